@@ -0,0 +1,331 @@
+use super::errors::Error;
+use super::game::{pgn_tag, Game};
+use super::position::Position;
+use super::{Action, Color};
+use std::fmt;
+
+/// Centipawn loss from which a move counts as an inaccuracy.
+const INACCURACY: i32 = 50;
+
+/// Centipawn loss from which a move counts as a mistake.
+const MISTAKE: i32 = 100;
+
+/// Centipawn loss from which a move counts as a blunder.
+const BLUNDER: i32 = 300;
+
+/// Losses above this are capped for the average, so a single lost mate
+/// doesn't dominate the statistics.
+const MAX_LOSS: i32 = 1000;
+
+/// Engine evaluations around a single ply, in centipawns from the view of
+/// the player who moved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlyEval {
+    /// Evaluation of the engine's best move.
+    pub best: i32,
+    /// Evaluation after the move that was played.
+    pub played: i32,
+    /// Principal variation starting with the best move, in absolute coordinates.
+    /// Actions of a board with Black to play convert with `Action::absolute`.
+    pub line: Vec<Action>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveClass {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    pub fn from_loss(loss: i32) -> Self {
+        match loss {
+            l if l <= 0 => MoveClass::Best,
+            l if l < INACCURACY => MoveClass::Good,
+            l if l < MISTAKE => MoveClass::Inaccuracy,
+            l if l < BLUNDER => MoveClass::Mistake,
+            _ => MoveClass::Blunder,
+        }
+    }
+
+    /// Returns the numeric annotation glyph for weak moves: $6 (?!),
+    /// $2 (?) and $4 (??).
+    pub fn nag(&self) -> Option<u8> {
+        match self {
+            MoveClass::Best | MoveClass::Good => None,
+            MoveClass::Inaccuracy => Some(6),
+            MoveClass::Mistake => Some(2),
+            MoveClass::Blunder => Some(4),
+        }
+    }
+}
+
+impl fmt::Display for MoveClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            MoveClass::Best => "Best",
+            MoveClass::Good => "Good",
+            MoveClass::Inaccuracy => "Inaccuracy",
+            MoveClass::Mistake => "Mistake",
+            MoveClass::Blunder => "Blunder",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewedMove {
+    pub action: Action,
+    pub side: Color,
+    pub eval: PlyEval,
+    /// Centipawns lost compared to the best move, never negative.
+    pub loss: i32,
+    pub class: MoveClass,
+    /// Accuracy of the move from 0 to 100, based on the drop of the winning chances.
+    pub accuracy: f64,
+}
+
+/// Move by move review of a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Review<'a> {
+    game: &'a Game,
+    moves: Vec<ReviewedMove>,
+}
+
+/// Reviews a game given the engine evaluations of each of its plies.
+pub fn review<'a>(game: &'a Game, evals: &[PlyEval]) -> Result<Review<'a>, Error> {
+    if evals.len() != game.actions().len() {
+        return Err(Error::EvaluationMismatch);
+    }
+    let mut side = game.start_side();
+    let mut moves = Vec::with_capacity(evals.len());
+    for (action, eval) in game.actions().iter().zip(evals.iter()) {
+        let loss = (eval.best - eval.played).max(0);
+        moves.push(ReviewedMove {
+            action: *action,
+            side,
+            eval: eval.clone(),
+            loss,
+            class: MoveClass::from_loss(loss),
+            accuracy: accuracy(eval.best, eval.played),
+        });
+        side = match side {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+    }
+    Ok(Review { game, moves })
+}
+
+impl<'a> Review<'a> {
+    pub fn moves(&self) -> &[ReviewedMove] {
+        &self.moves
+    }
+
+    fn moves_of(&self, side: Color) -> impl Iterator<Item = &ReviewedMove> {
+        self.moves.iter().filter(move |m| m.side == side)
+    }
+
+    /// Returns the mean move accuracy of the player, None if they didn't move.
+    pub fn accuracy(&self, side: Color) -> Option<f64> {
+        let (count, sum) = self
+            .moves_of(side)
+            .fold((0, 0.0), |(count, sum), m| (count + 1, sum + m.accuracy));
+        if count == 0 {
+            None
+        } else {
+            Some(sum / f64::from(count))
+        }
+    }
+
+    pub fn average_centipawn_loss(&self, side: Color) -> Option<f64> {
+        let (count, sum) = self.moves_of(side).fold((0, 0), |(count, sum), m| {
+            (count + 1, sum + m.loss.min(MAX_LOSS))
+        });
+        if count == 0 {
+            None
+        } else {
+            Some(f64::from(sum) / f64::from(count))
+        }
+    }
+
+    pub fn count(&self, side: Color, class: MoveClass) -> usize {
+        self.moves_of(side).filter(|m| m.class == class).count()
+    }
+
+    /// Returns the game in PGN layout with NAGs on weak moves and the engine
+    /// line in a comment. The tags of the Seven Tag Roster that aren't known
+    /// are written as `?`, and the start position is given by the SetUp and
+    /// FEN tags.
+    ///
+    /// The movetext isn't SAN but coordinate notation, e.g. `e2e4`, as the
+    /// board doesn't model all pieces. Strict PGN readers won't accept it.
+    pub fn to_pgn(&self, white: &str, black: &str) -> String {
+        let result = self.game.result();
+        let start_side = self.game.start_side();
+        let mut pgn = String::new();
+        pgn.push_str(&pgn_tag("Event", "?"));
+        pgn.push_str(&pgn_tag("Site", "?"));
+        pgn.push_str(&pgn_tag("Date", "????.??.??"));
+        pgn.push_str(&pgn_tag("Round", "?"));
+        pgn.push_str(&pgn_tag("White", white));
+        pgn.push_str(&pgn_tag("Black", black));
+        pgn.push_str(&pgn_tag("Result", result));
+        pgn.push_str(&pgn_tag("SetUp", 1));
+        pgn.push_str(&pgn_tag("FEN", self.game.start().fen(start_side)));
+        for (side, name) in [(Color::White, "White"), (Color::Black, "Black")] {
+            if let Some(accuracy) = self.accuracy(side) {
+                let tag = format!("{}Accuracy", name);
+                pgn.push_str(&pgn_tag(&tag, format!("{:.1}", accuracy)));
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = vec![];
+        let mut number = 1;
+        // Black's move needs its number again after a comment
+        let mut commented = true;
+        for m in self.moves.iter() {
+            match m.side {
+                Color::White => tokens.push(format!("{}.", number)),
+                Color::Black if commented => tokens.push(format!("{}...", number)),
+                Color::Black => {}
+            }
+            tokens.push(coordinate(&m.action));
+            commented = false;
+            if let Some(nag) = m.class.nag() {
+                tokens.push(format!("${}", nag));
+                let line: Vec<String> = m.eval.line.iter().map(coordinate).collect();
+                tokens.push(format!(
+                    "{{ {}, {:+.2}. Best was {} ({:+.2}). }}",
+                    m.class,
+                    f64::from(m.eval.played) / 100.0,
+                    line.join(" "),
+                    f64::from(m.eval.best) / 100.0
+                ));
+                commented = true;
+            }
+            if m.side == Color::Black {
+                number += 1;
+            }
+        }
+        tokens.push(result.to_string());
+        pgn.push_str(&tokens.join(" "));
+        pgn.push('\n');
+        pgn
+    }
+}
+
+// accuracy maps the drop of the winning chances to a 0 to 100 move accuracy
+fn accuracy(best: i32, played: i32) -> f64 {
+    let drop = (win_percent(best) - win_percent(played)).max(0.0);
+    (103.1668 * (-0.04354 * drop).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+// win_percent converts a centipawn evaluation to winning chances
+fn win_percent(centipawns: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.003_682_08 * f64::from(centipawns)).exp()) - 1.0)
+}
+
+fn square(position: Position) -> String {
+    format!("{}{}", (b'a' + position.x as u8) as char, position.y + 1)
+}
+
+fn coordinate(action: &Action) -> String {
+    square(action.source) + &square(action.destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::random_game;
+
+    fn eval(best: i32, played: i32) -> PlyEval {
+        PlyEval {
+            best,
+            played,
+            line: vec![],
+        }
+    }
+
+    #[test]
+    fn classification() {
+        assert_eq!(MoveClass::Best, MoveClass::from_loss(0));
+        assert_eq!(MoveClass::Good, MoveClass::from_loss(49));
+        assert_eq!(MoveClass::Inaccuracy, MoveClass::from_loss(50));
+        assert_eq!(MoveClass::Mistake, MoveClass::from_loss(150));
+        assert_eq!(MoveClass::Blunder, MoveClass::from_loss(300));
+        assert_eq!(Some(4), MoveClass::Blunder.nag());
+        assert_eq!(None, MoveClass::Good.nag());
+
+        assert!(accuracy(30, 30) > 99.99);
+        assert_eq!(accuracy(30, 30), accuracy(30, 80));
+        assert!(accuracy(0, -100) < accuracy(0, -50));
+        assert!(accuracy(600, 500) > accuracy(0, -100));
+    }
+
+    #[test]
+    fn review_statistics() {
+        let game = random_game(7, 4);
+        assert!(matches!(review(&game, &[]), Err(Error::EvaluationMismatch)));
+
+        let evals = [eval(20, 20), eval(-20, -90), eval(40, -300), eval(0, -10)];
+        let review = review(&game, &evals).unwrap();
+        let classes: Vec<MoveClass> = review.moves().iter().map(|m| m.class).collect();
+        assert_eq!(
+            vec![
+                MoveClass::Best,
+                MoveClass::Inaccuracy,
+                MoveClass::Blunder,
+                MoveClass::Good
+            ],
+            classes
+        );
+        assert_eq!(Color::Black, review.moves()[1].side);
+        assert_eq!(Some(170.0), review.average_centipawn_loss(Color::White));
+        assert_eq!(Some(40.0), review.average_centipawn_loss(Color::Black));
+        assert_eq!(1, review.count(Color::White, MoveClass::Blunder));
+        assert!(review.accuracy(Color::White).unwrap() < review.accuracy(Color::Black).unwrap());
+    }
+
+    #[test]
+    fn annotated_pgn() {
+        let game = random_game(3, 3);
+        let first = coordinate(&game.actions()[0]);
+        let evals = [
+            PlyEval {
+                best: 30,
+                played: -150,
+                line: game.actions()[1..].to_vec(),
+            },
+            eval(150, 150),
+            eval(-150, -150),
+        ];
+        let pgn = review(&game, &evals)
+            .unwrap()
+            .to_pgn("Ann \"The Rook\"", "Bob");
+        let lines: Vec<&str> = pgn.lines().collect();
+        assert_eq!("[Event \"?\"]", lines[0]);
+        assert_eq!("[Date \"????.??.??\"]", lines[2]);
+        assert_eq!("[White \"Ann \\\"The Rook\\\"\"]", lines[4]);
+        assert_eq!("[Result \"*\"]", lines[6]);
+        assert_eq!("[SetUp \"1\"]", lines[7]);
+        assert_eq!(
+            "[FEN \"8/pppppppp/8/8/8/8/PPPPPPPP/8 w - - 0 1\"]",
+            lines[8]
+        );
+        assert!(lines[9].starts_with("[WhiteAccuracy \""));
+        assert_eq!("[BlackAccuracy \"100.0\"]", lines[10]);
+        let movetext = lines[12];
+        let second = coordinate(&game.actions()[1]);
+        assert!(
+            movetext.starts_with(&format!("1. {} $2 {{ Mistake, -1.50. Best was ", first)),
+            "{}",
+            movetext
+        );
+        assert!(movetext.contains(&format!("(+0.30). }} 1... {} 2. ", second)));
+        assert!(movetext.contains(" 2. "));
+        assert!(movetext.ends_with(" *"));
+    }
+}
@@ -1,241 +1,237 @@
+use super::diagram::Diagram;
+use super::errors::Error;
 use super::pieces::Piece;
-use super::Action;
-
-struct Board {
+use super::position::Position;
+use super::{Action, Color};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// Represents a board from perspective of the player that is about to play, hence
+/// my_pieces and enemy_pieces fields.
+#[derive(Clone, std::fmt::Debug, PartialEq, Eq)]
+pub struct Board {
     my_pieces: Vec<Piece>,
     enemy_pieces: Vec<Piece>,
 }
 
 impl Board {
-    fn legal_moves(&self) -> Vec<Action> {
+    pub fn new(my_pieces: Vec<Piece>, enemy_pieces: Vec<Piece>) -> Self {
+        Self {
+            my_pieces,
+            enemy_pieces,
+        }
+    }
+
+    /// Returns the pieces indexed by absolute square (a1 = 0, h8 = 63) along with
+    /// their color. Positions of the board are relative to the player about to play,
+    /// so they are mirrored vertically when that player is Black.
+    pub fn squares(&self, side: Color) -> [Option<(Color, &Piece)>; 64] {
+        let enemy = match side {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let mut squares = [None; 64];
+        for (color, pieces) in [(side, &self.my_pieces), (enemy, &self.enemy_pieces)].iter() {
+            for piece in pieces.iter() {
+                let pos = piece.position().absolute(side);
+                squares[pos.arr_pos()] = Some((*color, piece));
+            }
+        }
+        squares
+    }
+
+    /// Returns the position as FEN with side the player about to play. Castling
+    /// and en passant aren't modelled, so those fields are always `-`.
+    pub fn fen(&self, side: Color) -> String {
+        let squares = self.squares(side);
+        let mut ranks = Vec::with_capacity(8);
+        for y in (0..8).rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for x in 0..8 {
+                match squares[Position::new(x, y).arr_pos()] {
+                    Some((color, piece)) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        rank.push(piece.letter(color));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            ranks.push(rank);
+        }
+        let side = match side {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        format!("{} {} - - 0 1", ranks.join("/"), side)
+    }
+
+    /// Returns a configurable text diagram of the board.
+    pub fn diagram(&self) -> Diagram<'_> {
+        Diagram::new(self)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Action> {
         self.my_pieces
             .iter()
             .flat_map(|my_piece| my_piece.legal_moves())
             .flat_map(|ray| ray.move_actions(&self.my_pieces, &self.enemy_pieces))
             .collect()
     }
-    fn legal_strikes(&self) -> Vec<Action> {
+    pub fn legal_strikes(&self) -> Vec<Action> {
         self.my_pieces
             .iter()
             .flat_map(|my_piece| my_piece.legal_strikes())
             .flat_map(|ray| ray.strike_actions(&self.my_pieces, &self.enemy_pieces))
             .collect()
     }
+
+    /// Returns all legal moves and strikes in a current turn.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let mut actions = self.legal_moves();
+        actions.extend(self.legal_strikes());
+        actions
+    }
+
+    /// Picks one of the legal actions uniformly at random. Returns None if there
+    /// is no legal action.
+    pub fn random_move(&self, rng: &mut impl Rng) -> Option<Action> {
+        self.legal_actions().choose(rng).copied()
+    }
+
+    /// Executes given action. Yields error if action is not valid.
+    pub fn play(&mut self, action: &Action) -> Result<(), Error> {
+        if !self.legal_actions().iter().any(|a| a == action) {
+            return Err(Error::InvalidAction);
+        }
+        // remove enemy piece if the action destination collides with it
+        self.enemy_pieces
+            .retain(|piece| !piece.collides_with(action.destination));
+
+        // set position of the piece to the destination
+        if let Some(piece) = self
+            .my_pieces
+            .iter_mut()
+            .find(|piece| piece.collides_with(action.source))
+        {
+            piece.set_position(action.destination);
+        }
+        Ok(())
+    }
+
+    /// Mutates internal state to swap the sides. This is usually used after each turn.
+    pub fn swap_sides(&mut self) {
+        std::mem::swap(&mut self.my_pieces, &mut self.enemy_pieces);
+        swap_positions(&mut self.enemy_pieces);
+        swap_positions(&mut self.my_pieces);
+    }
+}
+
+fn swap_positions(pieces: &mut [Piece]) {
+    for piece in pieces.iter_mut() {
+        piece.set_position(piece.position().flip());
+    }
+}
+
+impl Default for Board {
+    /// Returns pawns in their starting position, White about to play. Other
+    /// pieces are not modelled yet.
+    fn default() -> Self {
+        let pawns = |y| (0..8).map(|x| Piece::Pawn(Position::new(x, y))).collect();
+        Board::new(pawns(1), pawns(6))
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.diagram().fmt(f)
+    }
+}
+
+#[test]
+fn board_legal_actions() {
+    let p1_pos = Position { x: 0, y: 1 };
+    let p2_pos = Position { x: 3, y: 2 };
+    let p3_pos = Position { x: 1, y: 2 };
+    let board = Board::new(
+        vec![Piece::Pawn(p1_pos), Piece::Pawn(p2_pos)],
+        vec![Piece::Pawn(p3_pos)],
+    );
+
+    let expected = vec![
+        Action::new(p1_pos, Position::new(0, 2)),
+        Action::new(p1_pos, Position::new(0, 3)),
+        Action::new(p2_pos, Position::new(3, 3)),
+        Action::new(p1_pos, Position::new(1, 2)),
+    ];
+    assert_eq!(expected, board.legal_actions());
+}
+
+#[test]
+fn play() {
+    let mut board = Board::new(
+        vec![Piece::Pawn(Position::new(0, 1))],
+        vec![Piece::Pawn(Position::new(1, 2))],
+    );
+    assert!(board
+        .play(&Action::new(Position::new(0, 1), Position::new(0, 4)))
+        .is_err());
+    board
+        .play(&Action::new(Position::new(0, 1), Position::new(1, 2)))
+        .unwrap();
+    assert_eq!(
+        Board::new(vec![Piece::Pawn(Position::new(1, 2))], vec![]),
+        board
+    );
+}
+
+#[test]
+fn fen() {
+    let board = Board::default();
+    assert_eq!(
+        "8/pppppppp/8/8/8/8/PPPPPPPP/8 w - - 0 1",
+        board.fen(Color::White)
+    );
+    let board = Board::new(
+        vec![Piece::Pawn(Position::new(3, 1))],
+        vec![Piece::Pawn(Position::new(4, 3))],
+    );
+    // positions are seen from Black, so d2 is d7 and e4 is e5
+    assert_eq!("8/3p4/8/4P3/8/8/8/8 b - - 0 1", board.fen(Color::Black));
 }
 
-// use super::errors::Error;
-// use super::position::Position;
-// use std::fmt;
-
-// /// Represents a board from perspective of the player that is about to play, hence
-// /// my_pieces and enemy_pieces fields.
-// pub struct Board {
-//     pub my_pieces: Vec<Box<dyn Piece>>,
-//     pub enemy_pieces: Vec<Box<dyn Piece>>,
-// }
-
-// impl Board {
-//     /// Returns Some with a reference to my own colliding piece on a given position.
-//     /// Returns None if there is no colliding my piece.
-//     pub fn my_collision(&self, position: Position) -> Option<&Box<dyn Piece>> {
-//         for piece in self.my_pieces.iter() {
-//             if piece.get_position() == position {
-//                 return Some(piece);
-//             }
-//         }
-//         None
-//     }
-
-//     /// Returns Some with a reference to enemy colliding piece on a given position.
-//     /// Returns None if there is no colliding enemy piece.
-//     pub fn enemy_collision(&self, position: Position) -> Option<&Box<dyn Piece>> {
-//         for piece in self.enemy_pieces.iter() {
-//             if piece.get_position() == position {
-//                 return Some(piece);
-//             }
-//         }
-//         None
-//     }
-//     /// Returns Some with a reference to a colliding piece on a given [position].
-//     /// Returns None if there is no colliding piece.
-//     pub fn collision(&self, position: Position) -> Option<&Box<dyn Piece>> {
-//         if let Some(my_piece) = self.my_collision(position) {
-//             Some(my_piece)
-//         } else if let Some(enemy_piece) = self.enemy_collision(position) {
-//             Some(enemy_piece)
-//         } else {
-//             None
-//         }
-//     }
-
-//     /// Returns Vec of all possible actions in a current turn
-//     pub fn possible_actions(&self) -> Vec<Action> {
-//         let mut actions = Vec::new();
-//         for piece in self.my_pieces.iter() {
-//             for action in piece.possible_actions(&self) {
-//                 actions.push(Action {
-//                     source: piece.get_position(),
-//                     destination: action,
-//                 });
-//             }
-//         }
-//         actions
-//     }
-
-//     /// Mutates internal state to swap the sides. This is usually used after each turn.
-//     pub fn swap_sides(&mut self) {
-//         std::mem::swap(&mut self.my_pieces, &mut self.enemy_pieces);
-//         swap_positions(&mut self.enemy_pieces);
-//         swap_positions(&mut self.my_pieces);
-//     }
-
-//     /// Executes given action. Yields error if action is not valid.
-//     pub fn play(&mut self, action: &Action) -> Result<(), Error> {
-//         if !self.possible_actions().iter().any(|a| a == action) {
-//             return Err(Error::InvalidAction);
-//         }
-//         // remove enemy piece if the action destination collides with it
-//         if let Some(enemy_index) = self
-//             .enemy_pieces
-//             .iter()
-//             .position(|piece| piece.get_position() == action.destination)
-//         {
-//             self.enemy_pieces.remove(enemy_index);
-//         }
-
-//         // set position of the piece to the destination
-//         self.my_pieces
-//             .iter_mut()
-//             .find(|piece| piece.get_position() == action.source)
-//             .unwrap()
-//             .set_position(action.destination);
-//         Ok(())
-//     }
-
-//     pub fn check_position(&self, pos: Position) -> bool {
-//         // self.swap_sides();
-//         let is_empty = self
-//             .enemy_pieces
-//             .iter()
-//             .map(|enemy_piece| {
-//                 enemy_piece
-//                     .allowed_strike_destinations(self)
-//                     .into_iter()
-//                     .find(|&p| pos == p)
-//             })
-//             .flatten()
-//             .collect::<Vec<Position>>()
-//             .is_empty();
-//         // self.swap_sides();
-//         is_empty
-//     }
-// }
-
-// impl fmt::Display for Board {
-//     // https://chess.stackexchange.com/questions/1600/chess-program-for-linux-unix-console
-//     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-//         let my_pieces_space = to_space(&self.my_pieces);
-//         let enemy_pieces_space = to_space(&self.enemy_pieces);
-
-//         let board_strs: Vec<String> = my_pieces_space
-//             .iter()
-//             .zip(enemy_pieces_space.iter())
-//             .rev()
-//             .map(|(my_place, enemy_place)| match (my_place, enemy_place) {
-//                 (Some(piece), None) => piece.icon().light.to_string(),
-//                 (None, Some(piece)) => piece.icon().dark.to_string(),
-//                 _ => ".".to_string(),
-//             })
-//             .collect();
-
-//         f.write_str("  a b c d e f g h\n")?;
-//         for (row_index, row) in board_strs.chunks(8).enumerate() {
-//             f.write_fmt(format_args!(
-//                 "{} {} {} {} {} {} {} {} {} {}\n",
-//                 8 - row_index,
-//                 row[7],
-//                 row[6],
-//                 row[5],
-//                 row[4],
-//                 row[3],
-//                 row[2],
-//                 row[1],
-//                 row[0],
-//                 8 - row_index,
-//             ))?;
-//         }
-//         f.write_str("  a b c d e f g h\n")?;
-//         Ok(())
-//     }
-// }
-
-// #[test]
-// fn board_possible_actions() {
-//     use super::pieces::Pawn;
-
-//     let p1_pos = Position { x: 0, y: 1 };
-//     let p2_pos = Position { x: 3, y: 2 };
-//     let p3_pos = Position { x: 1, y: 2 };
-//     let p1 = Pawn::new(p1_pos, true);
-//     let p2 = Pawn::new(p2_pos, false);
-//     let p3 = Pawn::new(p3_pos, false);
-//     let board = Board {
-//         my_pieces: vec![Box::new(p1), Box::new(p2)],
-//         enemy_pieces: vec![Box::new(p3)],
-//     };
-
-//     let expected = vec![
-//         Action {
-//             source: p1_pos,
-//             destination: Position::new(0, 2),
-//         },
-//         Action {
-//             source: p1_pos,
-//             destination: Position::new(0, 3),
-//         },
-//         Action {
-//             source: p1_pos,
-//             destination: Position::new(1, 2),
-//         },
-//         Action {
-//             source: p2_pos,
-//             destination: Position::new(3, 3),
-//         },
-//     ];
-//     assert_eq!(expected, board.possible_actions());
-// }
-
-// #[test]
-// fn swap_sides() {
-//     use super::pieces::Pawn;
-//     let p1 = Pawn::new(Position { x: 0, y: 1 }, true);
-//     let p2 = Pawn::new(Position { x: 1, y: 1 }, true);
-//     let p3 = Pawn::new(Position { x: 0, y: 6 }, true);
-//     let mut board = Board {
-//         my_pieces: vec![Box::new(p1), Box::new(p2)],
-//         enemy_pieces: vec![Box::new(p3)],
-//     };
-//     board.swap_sides();
-//     let expected: Vec<Box<dyn Piece>> = vec![Box::new(Pawn::new(Position { x: 0, y: 1 }, true))];
-//     assert_eq!(expected, board.my_pieces);
-//     let expected: Vec<Box<dyn Piece>> = vec![
-//         Box::new(Pawn::new(Position { x: 0, y: 6 }, true)),
-//         Box::new(Pawn::new(Position { x: 1, y: 6 }, true)),
-//     ];
-//     assert_eq!(expected, board.enemy_pieces);
-// }
-
-// // #[test]
-// // fn check_position_simple() {
-// //     use super::pieces::Pawn;
-// //     let p1 = Pawn::new(Position { x: 0, y: 1 }, true);
-// //     let p2 = Pawn::new(Position { x: 1, y: 1 }, true);
-// //     let p3 = Pawn::new(Position { x: 0, y: 6 }, true);
-// //     let mut board = Board {
-// //         my_pieces: vec![Box::new(p1), Box::new(p2)],
-// //         enemy_pieces: vec![Box::new(p3)],
-// //     };
-
-// //     assert_eq!(true, board.check_position(Position { x: 1, y: 5 }));
-// //     assert_ne!(true, board.check_position(Position { x: 0, y: 6 }));
-// // }
+#[test]
+fn swap_sides() {
+    let p1 = Piece::Pawn(Position { x: 0, y: 1 });
+    let p2 = Piece::Pawn(Position { x: 1, y: 1 });
+    let p3 = Piece::Pawn(Position { x: 0, y: 6 });
+    let mut board = Board::new(vec![p1, p2], vec![p3]);
+    board.swap_sides();
+    assert_eq!(vec![Piece::Pawn(Position { x: 0, y: 1 })], board.my_pieces);
+    assert_eq!(
+        vec![
+            Piece::Pawn(Position { x: 0, y: 6 }),
+            Piece::Pawn(Position { x: 1, y: 6 }),
+        ],
+        board.enemy_pieces
+    );
+}
+
+#[test]
+fn random_move() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let board = Board::default();
+    let action = board.random_move(&mut rng).unwrap();
+    assert!(board.legal_actions().contains(&action));
+    assert_eq!(None, Board::new(vec![], vec![]).random_move(&mut rng));
+}
@@ -0,0 +1,343 @@
+use super::errors::Error;
+use super::position::Position;
+use super::{Action, Color};
+
+/// Number of distinct Chess960 start positions.
+pub const POSITIONS: usize = 960;
+
+/// Index of the standard chess start position in Scharnagl numbering.
+pub const STANDARD: usize = 518;
+
+const FILES: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+
+// Knight placements on the five squares left after placing bishops and queen.
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// Notation of the castling field in FEN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastlingNotation {
+    /// X-FEN uses KQkq and falls back to rook files only when ambiguous.
+    XFen,
+    /// Shredder-FEN always names the files of the castling rooks.
+    Shredder,
+}
+
+/// Files of the rooks each color may still castle with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_king_side: Option<usize>,
+    pub white_queen_side: Option<usize>,
+    pub black_king_side: Option<usize>,
+    pub black_queen_side: Option<usize>,
+}
+
+/// Returns the back rank of the start position with given Scharnagl index as
+/// uppercase FEN letters from the a file to the h file.
+///
+/// # Examples
+/// ```
+/// use chess::chess960;
+///
+/// let rank: String = chess960::back_rank(chess960::STANDARD).unwrap().iter().collect();
+/// assert_eq!("RNBQKBNR", rank);
+/// ```
+pub fn back_rank(index: usize) -> Result<[char; 8], Error> {
+    if index >= POSITIONS {
+        return Err(Error::Chess960IndexOutOfRange);
+    }
+    let mut rank = [' '; 8];
+    let (n, light_bishop) = (index / 4, index % 4);
+    rank[light_bishop * 2 + 1] = 'B';
+    let (n, dark_bishop) = (n / 4, n % 4);
+    rank[dark_bishop * 2] = 'B';
+    let (n, queen) = (n / 6, n % 6);
+    place(&mut rank, queen, 'Q');
+    let (first, second) = KNIGHTS[n];
+    // the second knight is placed first so the first one keeps its empty square index
+    place(&mut rank, second, 'N');
+    place(&mut rank, first, 'N');
+    for &piece in ['R', 'K', 'R'].iter() {
+        place(&mut rank, 0, piece);
+    }
+    Ok(rank)
+}
+
+// place puts the piece on the nth empty square of the rank
+fn place(rank: &mut [char; 8], nth: usize, piece: char) {
+    if let Some(square) = rank.iter_mut().filter(|c| **c == ' ').nth(nth) {
+        *square = piece;
+    }
+}
+
+/// Returns the full FEN of the start position with given index.
+pub fn start_fen(index: usize, notation: CastlingNotation) -> Result<String, Error> {
+    let rank = back_rank(index)?;
+    let rights = start_castling_rights(&rank);
+    let pieces: String = rank.iter().collect();
+    Ok(format!(
+        "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w {} - 0 1",
+        pieces.to_ascii_lowercase(),
+        pieces,
+        castling_field(&rights, &rank, &rank, notation)
+    ))
+}
+
+/// Returns the castling rights of a start position, i.e. both colors may castle
+/// with both rooks.
+pub fn start_castling_rights(rank: &[char; 8]) -> CastlingRights {
+    let king = king_file(rank);
+    let queen_side = rank[..king].iter().position(|&c| c == 'R');
+    let king_side = rank[king..]
+        .iter()
+        .position(|&c| c == 'R')
+        .map(|file| file + king);
+    CastlingRights {
+        white_king_side: king_side,
+        white_queen_side: queen_side,
+        black_king_side: king_side,
+        black_queen_side: queen_side,
+    }
+}
+
+fn king_file(rank: &[char; 8]) -> usize {
+    rank.iter().position(|&c| c == 'K').unwrap_or(4)
+}
+
+/// Formats castling rights as the FEN castling field. The back ranks are
+/// needed to tell whether X-FEN can use KQkq for a rook.
+pub fn castling_field(
+    rights: &CastlingRights,
+    white_rank: &[char; 8],
+    black_rank: &[char; 8],
+    notation: CastlingNotation,
+) -> String {
+    let sides = [
+        (rights.white_king_side, 'K', white_rank, Color::White),
+        (rights.white_queen_side, 'Q', white_rank, Color::White),
+        (rights.black_king_side, 'K', black_rank, Color::Black),
+        (rights.black_queen_side, 'Q', black_rank, Color::Black),
+    ];
+    let field: String = sides
+        .iter()
+        .filter_map(|&(file, letter, rank, color)| {
+            let file = file?;
+            let symbol = match notation {
+                CastlingNotation::XFen if outermost_rook(rank, letter == 'K') == Some(file) => {
+                    letter
+                }
+                _ => FILES[file].to_ascii_uppercase(),
+            };
+            Some(match color {
+                Color::White => symbol,
+                Color::Black => symbol.to_ascii_lowercase(),
+            })
+        })
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+// outermost_rook finds the rook closest to the corner on given side of the king
+fn outermost_rook(rank: &[char; 8], king_side: bool) -> Option<usize> {
+    let king = king_file(rank);
+    let is_rook = |file: &usize| rank[*file].eq_ignore_ascii_case(&'R');
+    if king_side {
+        (king + 1..8).rev().find(is_rook)
+    } else {
+        (0..king).find(is_rook)
+    }
+}
+
+/// Parses a standard, X-FEN or Shredder-FEN castling field. The back ranks are
+/// needed to resolve KQkq to the outermost rook of each side.
+pub fn parse_castling_field(
+    field: &str,
+    white_rank: &[char; 8],
+    black_rank: &[char; 8],
+) -> Result<CastlingRights, Error> {
+    let mut rights = CastlingRights::default();
+    match field {
+        "-" => return Ok(rights),
+        // FEN writes "-" when nobody can castle
+        "" => return Err(Error::InvalidCastling),
+        _ => {}
+    }
+    for c in field.chars() {
+        let (rank, color) = if c.is_ascii_uppercase() {
+            (white_rank, Color::White)
+        } else {
+            (black_rank, Color::Black)
+        };
+        let king = king_file(rank);
+        let file = match c.to_ascii_lowercase() {
+            'k' => outermost_rook(rank, true),
+            'q' => outermost_rook(rank, false),
+            f @ 'a'..='h' => Some(f as usize - 'a' as usize)
+                .filter(|&file| rank[file].eq_ignore_ascii_case(&'R')),
+            _ => None,
+        }
+        .ok_or(Error::InvalidCastling)?;
+        let slot = match (color, file > king) {
+            (Color::White, true) => &mut rights.white_king_side,
+            (Color::White, false) => &mut rights.white_queen_side,
+            (Color::Black, true) => &mut rights.black_king_side,
+            (Color::Black, false) => &mut rights.black_queen_side,
+        };
+        // each side castles at most once per wing, e.g. KK is repeated
+        if slot.replace(file).is_some() {
+            return Err(Error::InvalidCastling);
+        }
+    }
+    Ok(rights)
+}
+
+/// Returns castling encoded as in UCI_Chess960, i.e. the king captures its own
+/// rook. Coordinates are absolute, as seen from White, see `Action::absolute`.
+pub fn castling_action(king_file: usize, rook_file: usize, color: Color) -> Action {
+    let y = back_rank_y(color);
+    Action::new(Position::new(king_file, y), Position::new(rook_file, y))
+}
+
+/// Returns the king and rook destinations after castling, which are the same
+/// as in standard chess regardless of the start files.
+pub fn castling_destinations(king_side: bool, color: Color) -> (Position, Position) {
+    let y = back_rank_y(color);
+    if king_side {
+        (Position::new(6, y), Position::new(5, y))
+    } else {
+        (Position::new(2, y), Position::new(3, y))
+    }
+}
+
+fn back_rank_y(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn rank_str(index: usize) -> String {
+        back_rank(index).unwrap().iter().collect()
+    }
+
+    #[test]
+    fn known_positions() {
+        assert_eq!("BBQNNRKR", rank_str(0));
+        assert_eq!("RNBQKBNR", rank_str(STANDARD));
+        assert_eq!("RKRNNQBB", rank_str(959));
+        assert!(back_rank(960).is_err());
+    }
+
+    #[test]
+    fn all_positions_valid_and_distinct() {
+        let mut seen = HashSet::new();
+        for index in 0..POSITIONS {
+            let rank = back_rank(index).unwrap();
+            let files =
+                |piece| -> Vec<usize> { (0..8).filter(|&file| rank[file] == piece).collect() };
+            let (bishops, rooks, king) = (files('B'), files('R'), files('K'));
+            assert_eq!(1, (bishops[0] + bishops[1]) % 2);
+            assert!(rooks[0] < king[0] && king[0] < rooks[1]);
+            assert_eq!(2, files('N').len());
+            assert_eq!(1, files('Q').len());
+            assert!(seen.insert(rank));
+        }
+    }
+
+    #[test]
+    fn fen_castling_fields() {
+        assert_eq!(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            start_fen(STANDARD, CastlingNotation::XFen).unwrap()
+        );
+        assert_eq!(
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1",
+            start_fen(0, CastlingNotation::XFen).unwrap()
+        );
+        assert_eq!(
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1",
+            start_fen(0, CastlingNotation::Shredder).unwrap()
+        );
+        // castling with the inner of two rooks makes X-FEN name the file
+        let rank = ['R', 'R', 'K', ' ', ' ', ' ', ' ', ' '];
+        let rights = CastlingRights {
+            white_queen_side: Some(1),
+            black_queen_side: Some(0),
+            ..CastlingRights::default()
+        };
+        let field = castling_field(&rights, &rank, &rank, CastlingNotation::XFen);
+        assert_eq!("Bq", field);
+        assert_eq!(rights, parse_castling_field(&field, &rank, &rank).unwrap());
+        let none = CastlingRights::default();
+        assert_eq!(
+            "-",
+            castling_field(&none, &rank, &rank, CastlingNotation::XFen)
+        );
+    }
+
+    #[test]
+    fn parse_fields() {
+        let rank = back_rank(0).unwrap();
+        let expected = start_castling_rights(&rank);
+        assert_eq!(
+            expected,
+            parse_castling_field("KQkq", &rank, &rank).unwrap()
+        );
+        assert_eq!(
+            expected,
+            parse_castling_field("HFhf", &rank, &rank).unwrap()
+        );
+        assert_eq!(
+            CastlingRights {
+                black_king_side: Some(7),
+                ..CastlingRights::default()
+            },
+            parse_castling_field("k", &rank, &rank).unwrap()
+        );
+        assert!(parse_castling_field("A", &rank, &rank).is_err());
+        assert!(parse_castling_field("X", &rank, &rank).is_err());
+        for field in ["", "KK", "Kqq", "KH"] {
+            assert!(matches!(
+                parse_castling_field(field, &rank, &rank),
+                Err(Error::InvalidCastling)
+            ));
+        }
+    }
+
+    #[test]
+    fn uci_castling() {
+        let rank = back_rank(0).unwrap();
+        let rights = start_castling_rights(&rank);
+        assert_eq!(
+            Action::new(Position::new(6, 0), Position::new(7, 0)),
+            castling_action(
+                king_file(&rank),
+                rights.white_king_side.unwrap(),
+                Color::White
+            )
+        );
+        assert_eq!(
+            (Position::new(2, 7), Position::new(3, 7)),
+            castling_destinations(false, Color::Black)
+        );
+    }
+}
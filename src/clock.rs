@@ -0,0 +1,552 @@
+use super::errors::Error;
+use super::time::Limits;
+use super::Color;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Source of monotonic time, measured from an arbitrary origin.
+pub trait TimeSource {
+    fn now(&self) -> Duration;
+}
+
+/// Wall clock time source.
+#[derive(Copy, Clone, Debug)]
+pub struct MonotonicTime {
+    origin: Instant,
+}
+
+impl Default for MonotonicTime {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for MonotonicTime {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Time source which only moves when advanced. Clones share the same time,
+/// so a test can keep one and hand the other to a clock.
+#[derive(Clone, Debug, Default)]
+pub struct ManualTime {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualTime {
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// How time is given back to a player for each move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Delay {
+    None,
+    /// The increment is added after every move.
+    Fischer(Duration),
+    /// Time used is given back after the move, up to the delay.
+    Bronstein(Duration),
+    /// The clock only starts running once the delay has passed.
+    Simple(Duration),
+}
+
+/// Period of a time control, e.g. 90 minutes for 40 moves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stage {
+    /// Moves to play within the stage, None (or zero) for the rest of the game.
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub delay: Delay,
+}
+
+impl Stage {
+    // move_limit returns the moves of the stage, treating zero as unlimited
+    fn move_limit(&self) -> Option<u32> {
+        self.moves.filter(|&moves| moves > 0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// Stages follow each other, the time of the next one is added once
+    /// the moves of the current one are played. The last stage repeats.
+    Stages(Vec<Stage>),
+    /// The time one player uses is added to the other's clock.
+    Hourglass(Duration),
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        Self::single(time, Delay::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        Self::single(time, Delay::Fischer(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        Self::single(time, Delay::Bronstein(delay))
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> Self {
+        Self::single(time, Delay::Simple(delay))
+    }
+
+    fn single(time: Duration, delay: Delay) -> Self {
+        TimeControl::Stages(vec![Stage {
+            moves: None,
+            time,
+            delay,
+        }])
+    }
+
+    // stage returns the stage a player is in after having played `moves`
+    fn stage(&self, moves: u32) -> Option<&Stage> {
+        let stages = match self {
+            TimeControl::Stages(stages) => stages,
+            TimeControl::Hourglass(_) => return None,
+        };
+        let mut played = moves;
+        for stage in stages.iter() {
+            match stage.move_limit() {
+                Some(n) if played >= n => played -= n,
+                _ => return Some(stage),
+            }
+        }
+        stages.last()
+    }
+
+    // moves_left returns the moves until the next time control, if any
+    fn moves_left(&self, moves: u32) -> Option<u32> {
+        let stages = match self {
+            TimeControl::Stages(stages) => stages,
+            TimeControl::Hourglass(_) => return None,
+        };
+        let mut played = moves;
+        for stage in stages.iter().chain(stages.last().into_iter().cycle()) {
+            let n = stage.move_limit()?;
+            if played < n {
+                return Some(n - played);
+            }
+            played -= n;
+        }
+        None
+    }
+
+    fn initial_time(&self) -> Duration {
+        match self {
+            TimeControl::Stages(stages) => stages.first().map_or(Duration::ZERO, |s| s.time),
+            TimeControl::Hourglass(time) => *time,
+        }
+    }
+}
+
+/// Chess clock of both players. White's clock runs first once started.
+///
+/// # Examples
+/// ```
+/// use chess::clock::{Clock, ManualTime, TimeControl};
+/// use chess::Color;
+/// use std::time::Duration;
+///
+/// let time = ManualTime::default();
+/// let mut clock = Clock::new(
+///     TimeControl::fischer(Duration::from_secs(180), Duration::from_secs(2)),
+///     time.clone(),
+/// );
+/// clock.start();
+/// time.advance(Duration::from_secs(10));
+/// clock.press().unwrap();
+///
+/// assert_eq!(Duration::from_secs(172), clock.remaining(Color::White));
+/// assert_eq!(Color::Black, clock.side_to_move());
+/// ```
+pub struct Clock<T: TimeSource> {
+    control: TimeControl,
+    source: T,
+    remaining: [Duration; 2],
+    moves: [u32; 2],
+    side: Color,
+    started: Option<Duration>,
+    spent: Duration,
+    flagged: Option<Color>,
+}
+
+impl<T: TimeSource> Clock<T> {
+    pub fn new(control: TimeControl, source: T) -> Self {
+        let time = control.initial_time();
+        Self {
+            control,
+            source,
+            remaining: [time, time],
+            moves: [0, 0],
+            side: Color::White,
+            started: None,
+            spent: Duration::ZERO,
+            flagged: None,
+        }
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.side
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn moves(&self, side: Color) -> u32 {
+        self.moves[slot(side)]
+    }
+
+    /// Starts or resumes the clock of the side to move.
+    pub fn start(&mut self) {
+        if self.started.is_none() && self.flagged.is_none() {
+            self.started = Some(self.source.now());
+        }
+    }
+
+    /// Pauses the clock, e.g. for an arbiter's intervention.
+    pub fn stop(&mut self) {
+        self.spent = self.turn_elapsed();
+        self.started = None;
+    }
+
+    // turn_elapsed returns the time the side to move has been thinking
+    fn turn_elapsed(&self) -> Duration {
+        self.elapsed_at(self.source.now())
+    }
+
+    // elapsed_at returns the thinking time of the side to move at a given time
+    fn elapsed_at(&self, now: Duration) -> Duration {
+        self.spent
+            + self
+                .started
+                .map_or(Duration::ZERO, |started| now.saturating_sub(started))
+    }
+
+    // is_over reports whether the elapsed time exceeds the side to move's clock
+    fn is_over(&self, elapsed: Duration) -> bool {
+        self.charged(elapsed) > self.remaining[slot(self.side)]
+    }
+
+    // charged returns the part of the elapsed time taken from the clock
+    fn charged(&self, elapsed: Duration) -> Duration {
+        match self.control.stage(self.moves[slot(self.side)]) {
+            Some(Stage {
+                delay: Delay::Simple(delay),
+                ..
+            }) => elapsed.saturating_sub(*delay),
+            _ => elapsed,
+        }
+    }
+
+    /// Returns the time left on the player's clock, including the time
+    /// running for the side to move. Hourglass time flows to the waiting side.
+    pub fn remaining(&self, side: Color) -> Duration {
+        let elapsed = self.turn_elapsed();
+        let remaining = self.remaining[slot(side)];
+        if side == self.side {
+            remaining.saturating_sub(self.charged(elapsed))
+        } else if let TimeControl::Hourglass(_) = self.control {
+            remaining + elapsed.min(self.remaining[slot(self.side)])
+        } else {
+            remaining
+        }
+    }
+
+    /// Returns the side whose flag fell, if any.
+    pub fn flagged(&self) -> Option<Color> {
+        if self.flagged.is_some() {
+            return self.flagged;
+        }
+        if self.is_over(self.turn_elapsed()) {
+            Some(self.side)
+        } else {
+            None
+        }
+    }
+
+    /// Ends the move of the side to move and starts the opponent's clock.
+    /// Returns the time left to the player who moved. Fails when the flag
+    /// fell before the move was completed, which stops the clock.
+    pub fn press(&mut self) -> Result<Duration, Error> {
+        // the time is read once, so the flag check and the charge agree
+        let now = self.source.now();
+        let elapsed = self.elapsed_at(now);
+        if self.flagged.is_some() || self.is_over(elapsed) {
+            self.flagged = Some(self.flagged.unwrap_or(self.side));
+            self.started = None;
+            return Err(Error::FlagFell);
+        }
+        let me = slot(self.side);
+        let stage = self.control.stage(self.moves[me]).copied();
+        self.remaining[me] = self.remaining[me].saturating_sub(self.charged(elapsed));
+        match stage.map(|s| s.delay) {
+            Some(Delay::Fischer(increment)) => self.remaining[me] += increment,
+            Some(Delay::Bronstein(delay)) => self.remaining[me] += elapsed.min(delay),
+            _ => {}
+        }
+        if let TimeControl::Hourglass(_) = self.control {
+            self.remaining[1 - me] += elapsed;
+        }
+        self.moves[me] += 1;
+        if self.control.moves_left(self.moves[me] - 1) == Some(1) {
+            if let Some(next) = self.control.stage(self.moves[me]) {
+                self.remaining[me] += next.time;
+            }
+        }
+
+        let left = self.remaining[me];
+        self.side = match self.side {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        self.spent = Duration::ZERO;
+        if self.started.is_some() {
+            self.started = Some(now);
+        }
+        Ok(left)
+    }
+
+    /// Returns the current clock state as search limits for the side to move.
+    /// Only Fischer increments are reported, delays are left out.
+    pub fn limits(&self) -> Limits {
+        let increment = |side: Color| match self.control.stage(self.moves(side)) {
+            Some(Stage {
+                delay: Delay::Fischer(increment),
+                ..
+            }) => Some(*increment),
+            _ => None,
+        };
+        Limits {
+            wtime: Some(self.remaining(Color::White)),
+            btime: Some(self.remaining(Color::Black)),
+            winc: increment(Color::White),
+            binc: increment(Color::Black),
+            movestogo: self.control.moves_left(self.moves(self.side)),
+            ..Limits::default()
+        }
+    }
+}
+
+fn slot(side: Color) -> usize {
+    match side {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// Formats a clock reading as a PGN `%clk` command, e.g. `[%clk 1:29:57]`.
+pub fn clk_comment(remaining: Duration) -> String {
+    let seconds = remaining.as_secs();
+    format!(
+        "[%clk {}:{:02}:{:02}]",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    fn start(control: TimeControl) -> (Clock<ManualTime>, ManualTime) {
+        let time = ManualTime::default();
+        let mut clock = Clock::new(control, time.clone());
+        clock.start();
+        (clock, time)
+    }
+
+    // play lets the side to move think for the given time and press
+    fn play(clock: &mut Clock<ManualTime>, time: &ManualTime, seconds: u64) -> Duration {
+        time.advance(secs(seconds));
+        clock.press().unwrap()
+    }
+
+    #[test]
+    fn sudden_death_and_flag() {
+        let (mut clock, time) = start(TimeControl::sudden_death(secs(60)));
+        assert_eq!(secs(50), play(&mut clock, &time, 10));
+        time.advance(secs(20));
+        assert_eq!(secs(40), clock.remaining(Color::Black));
+        assert_eq!(secs(50), clock.remaining(Color::White));
+        assert_eq!(None, clock.flagged());
+
+        time.advance(secs(41));
+        assert_eq!(Duration::ZERO, clock.remaining(Color::Black));
+        assert_eq!(Some(Color::Black), clock.flagged());
+        assert!(matches!(clock.press(), Err(Error::FlagFell)));
+        assert!(!clock.is_running());
+        assert_eq!(Color::Black, clock.side_to_move());
+    }
+
+    #[test]
+    fn increments_and_delays() {
+        let (mut clock, time) = start(TimeControl::fischer(secs(60), secs(5)));
+        assert_eq!(secs(63), play(&mut clock, &time, 2));
+
+        let (mut clock, time) = start(TimeControl::bronstein(secs(60), secs(5)));
+        assert_eq!(secs(60), play(&mut clock, &time, 2));
+        assert_eq!(secs(55), play(&mut clock, &time, 10));
+
+        let (mut clock, time) = start(TimeControl::simple_delay(secs(60), secs(5)));
+        time.advance(secs(3));
+        assert_eq!(secs(60), clock.remaining(Color::White));
+        assert_eq!(secs(60), clock.press().unwrap());
+        assert_eq!(secs(53), play(&mut clock, &time, 12));
+        // the delay also counts before the flag falls
+        time.advance(secs(64));
+        assert_eq!(None, clock.flagged());
+        time.advance(secs(2));
+        assert_eq!(Some(Color::White), clock.flagged());
+    }
+
+    #[test]
+    fn multi_stage_control() {
+        // 40 moves in 90 minutes, then 30 minutes for the rest, 30s increment
+        let increment = Delay::Fischer(secs(30));
+        let control = TimeControl::Stages(vec![
+            Stage {
+                moves: Some(40),
+                time: secs(90 * 60),
+                delay: increment,
+            },
+            Stage {
+                moves: None,
+                time: secs(30 * 60),
+                delay: increment,
+            },
+        ]);
+        let (mut clock, time) = start(control);
+        assert_eq!(Some(40), clock.limits().movestogo);
+        for _ in 0..39 {
+            play(&mut clock, &time, 60);
+            play(&mut clock, &time, 0);
+        }
+        assert_eq!(Some(1), clock.limits().movestogo);
+        assert_eq!(secs(5400 - 39 * 30), clock.remaining(Color::White));
+        // the 40th move reaches the time control
+        let left = play(&mut clock, &time, 60);
+        assert_eq!(secs(5400 - 40 * 30 + 1800), left);
+        play(&mut clock, &time, 0);
+        assert_eq!(None, clock.limits().movestogo);
+        assert_eq!(left - secs(30), play(&mut clock, &time, 60));
+        assert_eq!(40, clock.moves(Color::Black));
+    }
+
+    #[test]
+    fn repeating_stage() {
+        // 5 minutes for every 10 moves
+        let control = TimeControl::Stages(vec![Stage {
+            moves: Some(10),
+            time: secs(300),
+            delay: Delay::None,
+        }]);
+        let (mut clock, time) = start(control);
+        for _ in 0..19 {
+            play(&mut clock, &time, 1);
+            play(&mut clock, &time, 0);
+        }
+        assert_eq!(secs(600 - 19), clock.remaining(Color::White));
+        assert_eq!(secs(900 - 20), play(&mut clock, &time, 1));
+        assert_eq!(Some(1), clock.limits().movestogo);
+    }
+
+    #[test]
+    fn zero_move_stage_is_unlimited() {
+        let control = TimeControl::Stages(vec![Stage {
+            moves: Some(0),
+            time: secs(60),
+            delay: Delay::None,
+        }]);
+        let (mut clock, time) = start(control);
+        assert_eq!(None, clock.limits().movestogo);
+        assert_eq!(secs(55), play(&mut clock, &time, 5));
+    }
+
+    // SteppingTime moves forward by a millisecond every time it is read
+    struct SteppingTime {
+        now: Cell<Duration>,
+    }
+
+    impl TimeSource for SteppingTime {
+        fn now(&self) -> Duration {
+            let now = self.now.get();
+            self.now.set(now + Duration::from_millis(1));
+            now
+        }
+    }
+
+    #[test]
+    fn press_reads_time_once() {
+        let time = SteppingTime {
+            now: Cell::new(Duration::ZERO),
+        };
+        let mut clock = Clock::new(TimeControl::sudden_death(Duration::from_millis(2)), time);
+        clock.start();
+        // the press happens exactly when the clock runs out
+        clock.source.now.set(Duration::from_millis(2));
+        assert_eq!(Ok(Duration::ZERO), clock.press().map_err(|_| ()));
+
+        // black's clock started at 2ms, the flag falls past 4ms
+        clock.source.now.set(Duration::from_millis(5));
+        assert!(matches!(clock.press(), Err(Error::FlagFell)));
+        assert_eq!(Some(Color::Black), clock.flagged());
+    }
+
+    #[test]
+    fn hourglass() {
+        let (mut clock, time) = start(TimeControl::Hourglass(secs(60)));
+        time.advance(secs(10));
+        assert_eq!(secs(50), clock.remaining(Color::White));
+        assert_eq!(secs(70), clock.remaining(Color::Black));
+        clock.press().unwrap();
+        assert_eq!(secs(65), play(&mut clock, &time, 5));
+        assert_eq!(secs(55), clock.remaining(Color::White));
+    }
+
+    #[test]
+    fn pause_and_limits() {
+        let (mut clock, time) = start(TimeControl::fischer(secs(300), secs(3)));
+        time.advance(secs(10));
+        clock.stop();
+        time.advance(secs(100));
+        assert_eq!(secs(290), clock.remaining(Color::White));
+        clock.start();
+        time.advance(secs(5));
+        assert_eq!(secs(288), clock.press().unwrap());
+        assert_eq!(
+            Limits {
+                wtime: Some(secs(288)),
+                btime: Some(secs(300)),
+                winc: Some(secs(3)),
+                binc: Some(secs(3)),
+                ..Limits::default()
+            },
+            clock.limits()
+        );
+    }
+
+    #[test]
+    fn clk_comments() {
+        assert_eq!("[%clk 1:29:57]", clk_comment(secs(5397)));
+        assert_eq!("[%clk 0:00:09]", clk_comment(Duration::from_millis(9_900)));
+    }
+}
@@ -0,0 +1,240 @@
+use super::board::Board;
+use super::position::Position;
+use super::{Action, Color};
+use std::fmt;
+
+const HIGHLIGHT: &str = "\x1b[30;43m";
+const RESET: &str = "\x1b[0m";
+const FILES: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+
+/// Text rendering of a board, see Board::diagram.
+///
+/// # Examples
+/// ```
+/// use chess::board::Board;
+/// use chess::pieces::Piece;
+/// use chess::position::Position;
+/// use chess::Color;
+///
+/// let board = Board::new(vec![Piece::Pawn(Position::new(4, 1))], vec![]);
+/// let diagram = board.diagram().coordinates(false).orientation(Color::Black);
+///
+/// assert_eq!(
+///     "........\n\
+///      ...P....\n\
+///      ........\n\
+///      ........\n\
+///      ........\n\
+///      ........\n\
+///      ........\n\
+///      ........\n",
+///     diagram.to_string()
+/// );
+/// ```
+pub struct Diagram<'a> {
+    board: &'a Board,
+    side: Color,
+    bottom: Color,
+    unicode: bool,
+    coordinates: bool,
+    colors: bool,
+    last_move: Option<&'a Action>,
+}
+
+impl<'a> Diagram<'a> {
+    /// Creates an ASCII diagram with coordinates, White to play and at the bottom.
+    pub fn new(board: &'a Board) -> Self {
+        Self {
+            board,
+            side: Color::White,
+            bottom: Color::White,
+            unicode: false,
+            coordinates: true,
+            colors: false,
+            last_move: None,
+        }
+    }
+
+    /// Sets the color of the player about to play, i.e. the owner of my_pieces.
+    pub fn side(mut self, side: Color) -> Self {
+        self.side = side;
+        self
+    }
+
+    /// Sets which color is drawn at the bottom of the diagram.
+    pub fn orientation(mut self, bottom: Color) -> Self {
+        self.bottom = bottom;
+        self
+    }
+
+    /// Uses Unicode figurines instead of FEN letters.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// Draws file letters and rank numbers around the board.
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Enables ANSI colors used for highlighting.
+    pub fn colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Highlights source and destination of the last move when colors are enabled.
+    /// The action is taken as the board generates it, i.e. relative to the player
+    /// about to play, see `side`.
+    pub fn last_move(mut self, action: &'a Action) -> Self {
+        self.last_move = Some(action);
+        self
+    }
+
+    // is_highlighted reports whether the square, in absolute coordinates, is part
+    // of the last move
+    fn is_highlighted(&self, pos: Position) -> bool {
+        // mirroring is its own inverse, so this converts into the board's coordinates
+        let pos = pos.absolute(self.side);
+        match self.last_move {
+            Some(action) => self.colors && (action.source == pos || action.destination == pos),
+            None => false,
+        }
+    }
+
+    fn write_files(&self, f: &mut fmt::Formatter<'_>, files: &[usize]) -> fmt::Result {
+        f.write_str(" ")?;
+        for &x in files {
+            write!(f, " {}", FILES[x])?;
+        }
+        f.write_str("\n")
+    }
+}
+
+impl<'a> fmt::Display for Diagram<'a> {
+    // https://chess.stackexchange.com/questions/1600/chess-program-for-linux-unix-console
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let squares = self.board.squares(self.side);
+        let (files, ranks): (Vec<usize>, Vec<usize>) = match self.bottom {
+            Color::White => ((0..8).collect(), (0..8).rev().collect()),
+            Color::Black => ((0..8).rev().collect(), (0..8).collect()),
+        };
+
+        if self.coordinates {
+            self.write_files(f, &files)?;
+        }
+        for &y in ranks.iter() {
+            if self.coordinates {
+                write!(f, "{} ", y + 1)?;
+            }
+            for (i, &x) in files.iter().enumerate() {
+                if self.coordinates && i > 0 {
+                    f.write_str(" ")?;
+                }
+                let pos = Position::new(x, y);
+                let symbol = match squares[pos.arr_pos()] {
+                    Some((color, piece)) if self.unicode => piece.figurine(color),
+                    Some((color, piece)) => piece.letter(color),
+                    None => '.',
+                };
+                if self.is_highlighted(pos) {
+                    write!(f, "{}{}{}", HIGHLIGHT, symbol, RESET)?;
+                } else {
+                    write!(f, "{}", symbol)?;
+                }
+            }
+            if self.coordinates {
+                write!(f, " {}", y + 1)?;
+            }
+            f.write_str("\n")?;
+        }
+        if self.coordinates {
+            self.write_files(f, &files)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pieces::Piece;
+
+    fn board() -> Board {
+        Board::new(
+            vec![
+                Piece::Pawn(Position::new(0, 1)),
+                Piece::Pawn(Position::new(4, 3)),
+            ],
+            vec![Piece::Pawn(Position::new(3, 6))],
+        )
+    }
+
+    #[test]
+    fn ascii_with_coordinates() {
+        let expected = "  a b c d e f g h\n\
+                        8 . . . . . . . . 8\n\
+                        7 . . . p . . . . 7\n\
+                        6 . . . . . . . . 6\n\
+                        5 . . . . . . . . 5\n\
+                        4 . . . . P . . . 4\n\
+                        3 . . . . . . . . 3\n\
+                        2 P . . . . . . . 2\n\
+                        1 . . . . . . . . 1\n  \
+                        a b c d e f g h\n";
+        assert_eq!(expected, board().to_string());
+    }
+
+    #[test]
+    fn black_to_play_flipped() {
+        let board = board();
+        let diagram = board
+            .diagram()
+            .side(Color::Black)
+            .orientation(Color::Black)
+            .unicode(true);
+        let expected = "  h g f e d c b a\n\
+                        1 . . . . . . . . 1\n\
+                        2 . . . . \u{2659} . . . 2\n\
+                        3 . . . . . . . . 3\n\
+                        4 . . . . . . . . 4\n\
+                        5 . . . \u{265F} . . . . 5\n\
+                        6 . . . . . . . . 6\n\
+                        7 . . . . . . . \u{265F} 7\n\
+                        8 . . . . . . . . 8\n  \
+                        h g f e d c b a\n";
+        assert_eq!(expected, diagram.to_string());
+    }
+
+    #[test]
+    fn highlight_last_move() {
+        let board = board();
+        let last_move = Action::new(Position::new(4, 1), Position::new(4, 3));
+        let diagram = board.diagram().coordinates(false).last_move(&last_move);
+        assert!(!diagram.to_string().contains(HIGHLIGHT));
+
+        let rendered = diagram.colors(true).to_string();
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert_eq!(format!("....{}P{}...", HIGHLIGHT, RESET), rows[4]);
+        assert_eq!(format!("P...{}.{}...", HIGHLIGHT, RESET), rows[6]);
+    }
+
+    #[test]
+    fn highlight_last_move_black_to_play() {
+        let board = board();
+        // e2e4 from Black's side is e7e5
+        let last_move = Action::new(Position::new(4, 1), Position::new(4, 3));
+        let rendered = board
+            .diagram()
+            .side(Color::Black)
+            .coordinates(false)
+            .colors(true)
+            .last_move(&last_move)
+            .to_string();
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert_eq!(format!("p...{}.{}...", HIGHLIGHT, RESET), rows[1]);
+        assert_eq!(format!("....{}p{}...", HIGHLIGHT, RESET), rows[3]);
+    }
+}
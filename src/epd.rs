@@ -0,0 +1,323 @@
+use super::errors::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Single EPD operation, e.g. `bm Nf3 Qd2;` or `id "WAC.001";`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Operation {
+    pub opcode: String,
+    pub operands: Vec<String>,
+}
+
+/// Extended Position Description record: the first four FEN fields followed
+/// by a list of operations.
+///
+/// # Examples
+/// ```
+/// use chess::epd::Epd;
+///
+/// let epd: Epd = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(Some("WAC.001"), epd.id());
+/// assert_eq!(vec!["Qg6"], epd.best_moves());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Epd {
+    pub placement: String,
+    pub side: String,
+    pub castling: String,
+    pub en_passant: String,
+    pub operations: Vec<Operation>,
+}
+
+impl Epd {
+    pub fn operation(&self, opcode: &str) -> Option<&Operation> {
+        self.operations.iter().find(|op| op.opcode == opcode)
+    }
+
+    fn operands(&self, opcode: &str) -> Vec<&str> {
+        self.operation(opcode)
+            .map(|op| op.operands.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn first_operand(&self, opcode: &str) -> Option<&str> {
+        self.operands(opcode).first().copied()
+    }
+
+    /// Returns the moves to find, in SAN (`bm`).
+    pub fn best_moves(&self) -> Vec<&str> {
+        self.operands("bm")
+    }
+
+    /// Returns the moves to avoid, in SAN (`am`).
+    pub fn avoid_moves(&self) -> Vec<&str> {
+        self.operands("am")
+    }
+
+    /// Returns the position identifier (`id`).
+    pub fn id(&self) -> Option<&str> {
+        self.first_operand("id")
+    }
+
+    /// Returns the primary comment (`c0`).
+    pub fn comment(&self) -> Option<&str> {
+        self.first_operand("c0")
+    }
+
+    /// Returns the analysis count depth (`acd`).
+    pub fn analysis_depth(&self) -> Option<u32> {
+        self.first_operand("acd")?.parse().ok()
+    }
+
+    /// Returns the centipawn evaluation (`ce`).
+    pub fn centipawn_eval(&self) -> Option<i32> {
+        self.first_operand("ce")?.parse().ok()
+    }
+
+    /// Returns the position as a full FEN, taking the move counters from the
+    /// `hmvc` and `fmvn` operations when present.
+    pub fn fen(&self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            self.placement,
+            self.side,
+            self.castling,
+            self.en_passant,
+            self.first_operand("hmvc").unwrap_or("0"),
+            self.first_operand("fmvn").unwrap_or("1")
+        )
+    }
+
+    /// Reports whether a move, in SAN, solves the position: it has to be one
+    /// of the `bm` moves and none of the `am` moves. Check and annotation
+    /// suffixes are ignored.
+    pub fn is_solved_by(&self, san: &str) -> bool {
+        let strip = |san: &str| san.trim_end_matches(|c| "+#!?".contains(c)).to_string();
+        let played = strip(san);
+        let best = self.best_moves();
+        let avoid = self.avoid_moves();
+        if best.is_empty() && avoid.is_empty() {
+            return false;
+        }
+        (best.is_empty() || best.iter().any(|m| strip(m) == played))
+            && !avoid.iter().any(|m| strip(m) == played)
+    }
+}
+
+impl FromStr for Epd {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let mut fields = Vec::with_capacity(4);
+        let mut rest = s;
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(Error::InvalidEpd);
+            }
+            fields.push(rest[..end].to_string());
+            rest = &rest[end..];
+        }
+        if fields[0].split('/').count() != 8 || (fields[1] != "w" && fields[1] != "b") {
+            return Err(Error::InvalidEpd);
+        }
+        Ok(Self {
+            placement: fields[0].clone(),
+            side: fields[1].clone(),
+            castling: fields[2].clone(),
+            en_passant: fields[3].clone(),
+            operations: parse_operations(rest)?,
+        })
+    }
+}
+
+// parse_operations splits `opcode operand...;` sequences, keeping quoted
+// operands (which may contain spaces and semicolons) together
+fn parse_operations(s: &str) -> Result<Vec<Operation>, Error> {
+    let mut operations = vec![];
+    let mut tokens: Vec<String> = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                if tokens.is_empty() {
+                    return Err(Error::InvalidEpd);
+                }
+                let opcode = tokens.remove(0);
+                operations.push(Operation {
+                    opcode,
+                    operands: std::mem::take(&mut tokens),
+                });
+            }
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err(Error::InvalidEpd),
+                    }
+                }
+                tokens.push(token);
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == ';' {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    if !tokens.is_empty() {
+        return Err(Error::InvalidEpd);
+    }
+    Ok(operations)
+}
+
+impl fmt::Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.placement, self.side, self.castling, self.en_passant
+        )?;
+        for op in self.operations.iter() {
+            write!(f, " {}", op.opcode)?;
+            for operand in op.operands.iter() {
+                if operand.is_empty()
+                    || operand.contains(char::is_whitespace)
+                    || operand.contains(';')
+                {
+                    write!(f, " \"{}\"", operand)?;
+                } else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+            f.write_str(";")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of a single test suite position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attempt {
+    pub id: String,
+    pub played: String,
+    pub solved: bool,
+}
+
+/// Tally of a test suite run such as WAC, STS or Bratko-Kopec.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub attempts: Vec<Attempt>,
+}
+
+impl Summary {
+    /// Records the move, in SAN, chosen for the position.
+    pub fn record(&mut self, epd: &Epd, played: &str) -> bool {
+        let solved = epd.is_solved_by(played);
+        self.attempts.push(Attempt {
+            id: epd.id().unwrap_or("?").to_string(),
+            played: played.to_string(),
+            solved,
+        });
+        solved
+    }
+
+    pub fn solved(&self) -> usize {
+        self.attempts.iter().filter(|a| a.solved).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.attempts.len() - self.solved()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for attempt in self.attempts.iter().filter(|a| !a.solved) {
+            writeln!(f, "failed {}: played {}", attempt.id, attempt.played)?;
+        }
+        write!(f, "solved {}/{}", self.solved(), self.attempts.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_record() {
+        let epd: Epd = "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - \
+                        bm Nf5 Qd2; am Nb5; id \"position; 1\"; c0 \"quiet  move\"; acd 12; ce -35; \
+                        c1 \"\";"
+            .parse()
+            .unwrap();
+        assert_eq!("w", epd.side);
+        assert_eq!("KQkq", epd.castling);
+        assert_eq!(vec!["Nf5", "Qd2"], epd.best_moves());
+        assert_eq!(vec!["Nb5"], epd.avoid_moves());
+        assert_eq!(Some("position; 1"), epd.id());
+        assert_eq!(Some("quiet  move"), epd.comment());
+        assert_eq!(Some(12), epd.analysis_depth());
+        assert_eq!(Some(-35), epd.centipawn_eval());
+        assert_eq!(None, epd.operation("pv"));
+        assert_eq!(Some(""), epd.first_operand("c1"));
+        assert_eq!(
+            "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 0 1",
+            epd.fen()
+        );
+        assert_eq!(epd, epd.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn move_counters_and_bare_positions() {
+        let epd: Epd = "8/8/8/8/8/8/8/8 b - e3 hmvc 4; fmvn 30;".parse().unwrap();
+        assert_eq!("8/8/8/8/8/8/8/8 b - e3 4 30", epd.fen());
+        let epd: Epd = "8/8/8/8/8/8/8/8 w - -".parse().unwrap();
+        assert!(epd.operations.is_empty());
+    }
+
+    #[test]
+    fn malformed_records() {
+        for record in [
+            "8/8/8/8 w - - bm e4;",
+            "8/8/8/8/8/8/8/8 x - -",
+            "8/8/8/8/8/8/8/8 w -",
+            "8/8/8/8/8/8/8/8 w - - bm e4",
+            "8/8/8/8/8/8/8/8 w - - id \"open;",
+            "8/8/8/8/8/8/8/8 w - - ;",
+        ]
+        .iter()
+        {
+            assert!(record.parse::<Epd>().is_err(), "{}", record);
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let bm: Epd = "8/8/8/8/8/8/8/8 w - - bm Qg6+; id \"a\";".parse().unwrap();
+        let am: Epd = "8/8/8/8/8/8/8/8 w - - am Bxh7; id \"b\";".parse().unwrap();
+        assert!(bm.is_solved_by("Qg6"));
+        assert!(!am.is_solved_by("Bxh7+"));
+        assert!(am.is_solved_by("Nf3"));
+
+        let mut summary = Summary::default();
+        assert!(summary.record(&bm, "Qg6+"));
+        assert!(!summary.record(&am, "Bxh7"));
+        assert_eq!((1, 1), (summary.solved(), summary.failed()));
+        assert_eq!("failed b: played Bxh7\nsolved 1/2", summary.to_string());
+    }
+}
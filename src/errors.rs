@@ -4,4 +4,28 @@ pub enum Error {
     PositionOutOfBounds,
     #[error("action is invalid")]
     InvalidAction,
+    #[error("chess960 start position index must be below 960")]
+    Chess960IndexOutOfRange,
+    #[error("invalid castling field in FEN")]
+    InvalidCastling,
+    #[error("EPD record is malformed")]
+    InvalidEpd,
+    #[error("opening book is malformed")]
+    InvalidBook,
+    #[error("position description is malformed")]
+    InvalidPosition,
+    #[error("tournament file is malformed")]
+    InvalidTournament,
+    #[error("current round is not finished yet")]
+    RoundNotFinished,
+    #[error("all rounds have been paired")]
+    TournamentFinished,
+    #[error("no such game in the tournament")]
+    UnknownGame,
+    #[error("flag fell before the move was completed")]
+    FlagFell,
+    #[error("number of evaluations doesn't match the game")]
+    EvaluationMismatch,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
@@ -0,0 +1,140 @@
+use super::board::Board;
+use super::{Action, Color};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+
+#[derive(Copy, Clone, Eq, PartialEq, std::fmt::Debug)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Unfinished,
+}
+
+impl fmt::Display for GameResult {
+    // formats the result as a PGN result token
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unfinished => "*",
+        })
+    }
+}
+
+/// Formats a PGN tag pair, escaping quotes and backslashes in the value.
+pub(crate) fn pgn_tag(name: &str, value: impl fmt::Display) -> String {
+    let value = value.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, value)
+}
+
+/// Record of a played game. Actions are stored in absolute coordinates, as seen
+/// from White, so they don't depend on which player was about to play.
+#[derive(Clone, std::fmt::Debug, PartialEq, Eq)]
+pub struct Game {
+    start: Board,
+    start_side: Color,
+    actions: Vec<Action>,
+    result: GameResult,
+}
+
+impl Game {
+    pub fn new(start: Board, start_side: Color) -> Self {
+        Self {
+            start,
+            start_side,
+            actions: vec![],
+            result: GameResult::Unfinished,
+        }
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn start_side(&self) -> Color {
+        self.start_side
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+}
+
+/// Plays uniformly random legal actions from the starting pawn position until
+/// the player about to play has no legal action or max_plies is reached. The
+/// same seed always yields the same game.
+///
+/// There are no kings yet, so a game can't be won. Running out of legal
+/// actions is scored as a draw and hitting the ply limit leaves the game
+/// unfinished.
+pub fn random_game(seed: u64, max_plies: usize) -> Game {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut board = Board::default();
+    let mut side = Color::White;
+    let mut game = Game::new(board.clone(), side);
+
+    while game.actions.len() < max_plies {
+        let action = match board.random_move(&mut rng) {
+            Some(action) => action,
+            None => {
+                game.result = GameResult::Draw;
+                return game;
+            }
+        };
+        // the action is legal as it was generated by the board
+        board.play(&action).expect("random move is legal");
+        game.actions.push(action.absolute(side));
+        board.swap_sides();
+        side = match side {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+    }
+    if board.legal_actions().is_empty() {
+        game.result = GameResult::Draw;
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_pgn_tags() {
+        assert_eq!("[Round \"3\"]\n", pgn_tag("Round", 3));
+        assert_eq!(
+            "[Event \"The \\\"A\\\" \\\\ B\"]\n",
+            pgn_tag("Event", "The \"A\" \\ B")
+        );
+    }
+
+    #[test]
+    fn random_game_is_reproducible() {
+        let game = random_game(42, 500);
+        assert_eq!(game, random_game(42, 500));
+        assert_eq!(GameResult::Draw, game.result());
+        assert!(!game.actions().is_empty());
+        assert_ne!(game.actions(), random_game(43, 500).actions());
+    }
+
+    #[test]
+    fn random_game_ply_limit() {
+        let game = random_game(1, 3);
+        assert_eq!(3, game.actions().len());
+        assert_eq!(GameResult::Unfinished, game.result());
+        assert_eq!(&Board::default(), game.start());
+        assert_eq!(Color::White, game.start_side());
+        // White pawns start on the second rank and Black ones on the seventh
+        assert_eq!(1, game.actions()[0].source.y);
+        assert_eq!(6, game.actions()[1].source.y);
+        assert_eq!(0, random_game(1, 0).actions().len());
+    }
+}
@@ -1,22 +1,60 @@
+pub mod analysis;
 pub mod board;
+pub mod chess960;
+pub mod clock;
+pub mod diagram;
+pub mod epd;
 pub mod errors;
+pub mod game;
 pub mod pieces;
+pub mod polyglot;
 pub mod position;
+pub mod rating;
 pub mod ray;
+pub mod stats;
+pub mod svg;
+pub mod time;
+pub mod tournament;
 
 use position::Position;
 
-#[derive(Eq, PartialEq, std::fmt::Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, std::fmt::Debug)]
+pub enum Color {
+    White,
+    Black,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, std::fmt::Debug)]
 pub struct Action {
     source: Position,
     destination: Position,
 }
 
 impl Action {
-    fn new(source: Position, destination: Position) -> Self {
+    pub fn new(source: Position, destination: Position) -> Self {
         Self {
             source,
             destination,
         }
     }
+
+    pub fn source(&self) -> Position {
+        self.source
+    }
+
+    pub fn destination(&self) -> Position {
+        self.destination
+    }
+
+    // absolute converts an action relative to the player about to play, as the
+    // board generates it, into the board coordinates seen from White
+    pub fn absolute(&self, side: Color) -> Action {
+        Action::new(self.source.absolute(side), self.destination.absolute(side))
+    }
+
+    // flip mirrors the action vertically, i.e. into the perspective of the other
+    // player. Flipping an absolute action of Black gives the one its board plays.
+    pub fn flip(&self) -> Action {
+        Action::new(self.source.flip(), self.destination.flip())
+    }
 }
@@ -1,6 +1,8 @@
 use super::position::{Direction, Position};
 use super::ray::Ray;
+use super::Color;
 
+#[derive(Clone, std::fmt::Debug, PartialEq, Eq)]
 pub enum Piece {
     Pawn(Position),
 }
@@ -16,6 +18,33 @@ impl Piece {
             Piece::Pawn(pos) => pawn_strikes(*pos),
         }
     }
+    pub fn position(&self) -> Position {
+        match self {
+            Piece::Pawn(pos) => *pos,
+        }
+    }
+    /// Returns the FEN letter of the piece, uppercase for White.
+    pub fn letter(&self, color: Color) -> char {
+        let letter = match self {
+            Piece::Pawn(_) => 'P',
+        };
+        match color {
+            Color::White => letter,
+            Color::Black => letter.to_ascii_lowercase(),
+        }
+    }
+    /// Returns the Unicode chess figurine of the piece.
+    pub fn figurine(&self, color: Color) -> char {
+        match (self, color) {
+            (Piece::Pawn(_), Color::White) => '\u{2659}',
+            (Piece::Pawn(_), Color::Black) => '\u{265F}',
+        }
+    }
+    pub fn set_position(&mut self, position: Position) {
+        match self {
+            Piece::Pawn(pos) => *pos = position,
+        }
+    }
     pub fn collides_with(&self, position: Position) -> bool {
         match self {
             Piece::Pawn(pos) => &position == pos,
@@ -24,25 +53,22 @@ impl Piece {
 }
 
 fn pawn_moves(position: Position) -> Vec<Ray> {
-    let starting_pos = match position.move_copy(Direction::Up, 1) {
-        Ok(pos) => pos,
-        Err(_) => return vec![],
-    };
+    if position.move_copy(Direction::Up, 1).is_err() {
+        return vec![];
+    }
 
     let mut ray_limit = 1;
     if position.y == 1 {
-        if let Ok(_) = position.move_copy(Direction::Up, 2) {
-            ray_limit = 2;
-        }
+        ray_limit = 2;
     }
-    vec![Ray::new(starting_pos, Direction::Up, ray_limit)]
+    vec![Ray::new(position, Direction::Up, ray_limit)]
 }
 
 fn pawn_strikes(position: Position) -> Vec<Ray> {
     vec![Direction::UpLeft, Direction::UpRight]
         .into_iter()
-        .flat_map(|direction| position.move_copy(direction, 1))
-        .map(|position| Ray::new_point(position))
+        .filter(|&direction| position.move_copy(direction, 1).is_ok())
+        .map(|direction| Ray::new(position, direction, 1))
         .collect()
 }
 
@@ -53,12 +79,12 @@ mod tests {
     fn pawn_legal_moves() {
         let pawn = Piece::Pawn(Position::new(3, 3));
         assert_eq!(
-            vec![Ray::new_from(3, 4, Direction::Up, 1)],
+            vec![Ray::new_from(3, 3, Direction::Up, 1)],
             pawn.legal_moves()
         );
         let pawn_starting = Piece::Pawn(Position::new(4, 1));
         assert_eq!(
-            vec![Ray::new_from(4, 2, Direction::Up, 2)],
+            vec![Ray::new_from(4, 1, Direction::Up, 2)],
             pawn_starting.legal_moves()
         );
     }
@@ -68,14 +94,14 @@ mod tests {
         let pawn = Piece::Pawn(Position::new(3, 3));
         assert_eq!(
             vec![
-                Ray::new_point(Position::new(2, 4)),
-                Ray::new_point(Position::new(4, 4))
+                Ray::new_from(3, 3, Direction::UpLeft, 1),
+                Ray::new_from(3, 3, Direction::UpRight, 1)
             ],
             pawn.legal_strikes()
         );
         let pawn = Piece::Pawn(Position::new(0, 3));
         assert_eq!(
-            vec![Ray::new_point(Position::new(1, 4))],
+            vec![Ray::new_from(0, 3, Direction::UpRight, 1)],
             pawn.legal_strikes()
         );
         let pawn = Piece::Pawn(Position::new(3, 7));
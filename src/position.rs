@@ -1,4 +1,5 @@
 use super::errors::Error;
+use super::Color;
 use std::fmt;
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -93,6 +94,20 @@ impl Position {
         }
     }
 
+    // absolute converts a position relative to the player about to play into
+    // the board coordinates seen from White
+    pub fn absolute(&self, side: Color) -> Position {
+        match side {
+            Color::White => *self,
+            Color::Black => self.flip(),
+        }
+    }
+
+    // flip mirrors the position vertically, i.e. into the perspective of the other player
+    pub fn flip(&self) -> Position {
+        Position::new(self.x, 7 - self.y)
+    }
+
     // arr_pos gives a current position as an index of 1D array
     pub fn arr_pos(&self) -> usize {
         self.y * 8 + self.x
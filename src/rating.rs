@@ -0,0 +1,461 @@
+use super::game::GameResult;
+use super::stats::{elo_to_score, score_to_elo};
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+/// Ratio between the Glicko and the Glicko-2 rating scale.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// Tolerance of the Glicko-2 volatility iteration.
+const CONVERGENCE: f64 = 0.000_001;
+
+/// Largest difference to the opponents' average a performance rating can
+/// show, reached with a perfect or a zero score.
+const PERFORMANCE_CAP: f64 = 800.0;
+
+/// Game result between two named players. Elo is updated after every game,
+/// Glicko-2 once per rating period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RatedGame {
+    pub event: String,
+    pub period: u32,
+    pub white: String,
+    pub black: String,
+    pub result: GameResult,
+}
+
+impl RatedGame {
+    // white_score returns the points of white, None for unfinished games
+    fn white_score(&self) -> Option<f64> {
+        match self.result {
+            GameResult::WhiteWins => Some(1.0),
+            GameResult::BlackWins => Some(0.0),
+            GameResult::Draw => Some(0.5),
+            GameResult::Unfinished => None,
+        }
+    }
+}
+
+/// Rating of a player after a game (Elo) or a rating period (Glicko-2).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RatingPoint {
+    pub event: String,
+    pub period: u32,
+    pub rating: f64,
+    /// Rating deviation, only tracked by Glicko-2.
+    pub deviation: Option<f64>,
+}
+
+/// Results of a player in a single event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Performance {
+    pub event: String,
+    pub player: String,
+    pub games: u32,
+    pub score: f64,
+    /// Sum of the opponents' ratings before each game.
+    pub opponents: f64,
+}
+
+impl Performance {
+    pub fn average_opponent(&self) -> f64 {
+        self.opponents / f64::from(self.games)
+    }
+
+    /// Returns the rating at which the score would be expected against
+    /// the average opponent.
+    pub fn rating(&self) -> f64 {
+        let score = self.score / f64::from(self.games);
+        let difference = if score <= 0.0 {
+            -PERFORMANCE_CAP
+        } else if score >= 1.0 {
+            PERFORMANCE_CAP
+        } else {
+            score_to_elo(score).clamp(-PERFORMANCE_CAP, PERFORMANCE_CAP)
+        };
+        self.average_opponent() + difference
+    }
+}
+
+/// Rating histories and event performances computed from a game collection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ratings {
+    histories: BTreeMap<String, Vec<RatingPoint>>,
+    performances: Vec<Performance>,
+}
+
+impl Ratings {
+    pub fn players(&self) -> impl Iterator<Item = &str> {
+        self.histories.keys().map(String::as_str)
+    }
+
+    pub fn history(&self, player: &str) -> &[RatingPoint] {
+        self.histories.get(player).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the latest rating of the player.
+    pub fn rating(&self, player: &str) -> Option<f64> {
+        self.history(player).last().map(|point| point.rating)
+    }
+
+    pub fn performances(&self) -> &[Performance] {
+        &self.performances
+    }
+
+    pub fn performance(&self, event: &str, player: &str) -> Option<&Performance> {
+        self.performances
+            .iter()
+            .find(|p| p.event == event && p.player == player)
+    }
+
+    fn push(&mut self, player: &str, point: RatingPoint) {
+        self.histories
+            .entry(player.to_string())
+            .or_default()
+            .push(point);
+    }
+
+    fn add_result(&mut self, event: &str, player: &str, score: f64, opponent: f64) {
+        let index = match self
+            .performances
+            .iter()
+            .position(|p| p.event == event && p.player == player)
+        {
+            Some(index) => index,
+            None => {
+                self.performances.push(Performance {
+                    event: event.to_string(),
+                    player: player.to_string(),
+                    games: 0,
+                    score: 0.0,
+                    opponents: 0.0,
+                });
+                self.performances.len() - 1
+            }
+        };
+        let performance = &mut self.performances[index];
+        performance.games += 1;
+        performance.score += score;
+        performance.opponents += opponent;
+    }
+}
+
+/// Elo rating with a fixed K-factor.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Elo {
+    pub k_factor: f64,
+    pub initial: f64,
+}
+
+impl Default for Elo {
+    fn default() -> Self {
+        Self {
+            k_factor: 20.0,
+            initial: 1500.0,
+        }
+    }
+}
+
+impl Elo {
+    /// Rates the games in order. Unfinished games are skipped.
+    pub fn rate(&self, games: &[RatedGame]) -> Ratings {
+        let mut ratings = Ratings::default();
+        let mut current: BTreeMap<&str, f64> = BTreeMap::new();
+        for game in games.iter() {
+            let score = match game.white_score() {
+                Some(score) => score,
+                None => continue,
+            };
+            let white = *current.entry(&game.white).or_insert(self.initial);
+            let black = *current.entry(&game.black).or_insert(self.initial);
+            let delta = self.k_factor * (score - elo_to_score(white - black));
+
+            ratings.add_result(&game.event, &game.white, score, black);
+            ratings.add_result(&game.event, &game.black, 1.0 - score, white);
+            for (player, rating) in [(&game.white, white + delta), (&game.black, black - delta)] {
+                current.insert(player, rating);
+                ratings.push(
+                    player,
+                    RatingPoint {
+                        event: game.event.clone(),
+                        period: game.period,
+                        rating,
+                        deviation: None,
+                    },
+                );
+            }
+        }
+        ratings
+    }
+}
+
+/// Glicko-2 rating on the Glicko scale.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlickoRating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+/// Glicko-2 rating system as described by Mark Glickman.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glicko2 {
+    /// System constant constraining the volatility change, usually 0.3 to 1.2.
+    pub tau: f64,
+    pub initial: GlickoRating,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Self {
+            tau: 0.5,
+            initial: GlickoRating {
+                rating: 1500.0,
+                deviation: 350.0,
+                volatility: 0.06,
+            },
+        }
+    }
+}
+
+impl Glicko2 {
+    /// Rates the games period by period, in ascending order. Players who
+    /// don't play in a period keep their rating but grow their deviation.
+    /// The history gets a point for every period a player played in.
+    pub fn rate(&self, games: &[RatedGame]) -> Ratings {
+        let mut periods: BTreeMap<u32, Vec<&RatedGame>> = BTreeMap::new();
+        for game in games.iter().filter(|g| g.white_score().is_some()) {
+            periods.entry(game.period).or_default().push(game);
+        }
+
+        let mut ratings = Ratings::default();
+        let mut current: BTreeMap<&str, GlickoRating> = BTreeMap::new();
+        for (&period, games) in periods.iter() {
+            let mut results: BTreeMap<&str, Vec<(GlickoRating, f64)>> = BTreeMap::new();
+            let mut events: BTreeMap<&str, &str> = BTreeMap::new();
+            for game in games.iter() {
+                let score = game.white_score().unwrap();
+                let white = *current.entry(&game.white).or_insert(self.initial);
+                let black = *current.entry(&game.black).or_insert(self.initial);
+                ratings.add_result(&game.event, &game.white, score, black.rating);
+                ratings.add_result(&game.event, &game.black, 1.0 - score, white.rating);
+                results.entry(&game.white).or_default().push((black, score));
+                results
+                    .entry(&game.black)
+                    .or_default()
+                    .push((white, 1.0 - score));
+                events.insert(&game.white, &game.event);
+                events.insert(&game.black, &game.event);
+            }
+
+            let updated: Vec<(&str, GlickoRating)> = current
+                .iter()
+                .map(|(&player, &rating)| {
+                    let results = results.get(player).map_or(&[][..], Vec::as_slice);
+                    (player, self.update(rating, results))
+                })
+                .collect();
+            for (player, rating) in updated {
+                current.insert(player, rating);
+                if let Some(event) = events.get(player) {
+                    ratings.push(
+                        player,
+                        RatingPoint {
+                            event: event.to_string(),
+                            period,
+                            rating: rating.rating,
+                            deviation: Some(rating.deviation),
+                        },
+                    );
+                }
+            }
+        }
+        ratings
+    }
+
+    /// Updates a rating with the results of one period, given as the
+    /// opponent's rating before the period and the points scored.
+    pub fn update(&self, player: GlickoRating, results: &[(GlickoRating, f64)]) -> GlickoRating {
+        let mu = (player.rating - 1500.0) / GLICKO2_SCALE;
+        let phi = player.deviation / GLICKO2_SCALE;
+        let sigma = player.volatility;
+        if results.is_empty() {
+            return GlickoRating {
+                deviation: (phi * phi + sigma * sigma).sqrt() * GLICKO2_SCALE,
+                ..player
+            };
+        }
+
+        let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt();
+        let mut inverse_variance = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in results.iter() {
+            let mu_j = (opponent.rating - 1500.0) / GLICKO2_SCALE;
+            let g_j = g(opponent.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-g_j * (mu - mu_j)).exp());
+            inverse_variance += g_j * g_j * expected * (1.0 - expected);
+            improvement += g_j * (score - expected);
+        }
+        let v = 1.0 / inverse_variance;
+        let delta = v * improvement;
+
+        let sigma = self.volatility(phi, sigma, v, delta);
+        let phi_star = (phi * phi + sigma * sigma).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let mu = mu + phi * phi * improvement;
+        GlickoRating {
+            rating: mu * GLICKO2_SCALE + 1500.0,
+            deviation: phi * GLICKO2_SCALE,
+            volatility: sigma,
+        }
+    }
+
+    // volatility finds the new volatility with the Illinois algorithm
+    fn volatility(&self, phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+        let a = (sigma * sigma).ln();
+        let tau2 = self.tau * self.tau;
+        let f = |x: f64| {
+            let ex = x.exp();
+            let d = phi * phi + v + ex;
+            ex * (delta * delta - d) / (2.0 * d * d) - (x - a) / tau2
+        };
+
+        let mut lower = a;
+        let mut upper = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * self.tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * self.tau
+        };
+        let (mut f_lower, mut f_upper) = (f(lower), f(upper));
+        while (upper - lower).abs() > CONVERGENCE {
+            let c = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_c = f(c);
+            if f_c * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = c;
+            f_upper = f_c;
+        }
+        (lower / 2.0).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(event: &str, period: u32, white: &str, black: &str, result: GameResult) -> RatedGame {
+        RatedGame {
+            event: event.to_string(),
+            period,
+            white: white.to_string(),
+            black: black.to_string(),
+            result,
+        }
+    }
+
+    #[test]
+    fn elo_updates() {
+        let games = [
+            game("Open", 1, "Ann", "Bob", GameResult::WhiteWins),
+            game("Open", 1, "Bob", "Cid", GameResult::Unfinished),
+            game("Open", 1, "Cid", "Ann", GameResult::Draw),
+        ];
+        let ratings = Elo::default().rate(&games);
+        assert_eq!(
+            vec!["Ann", "Bob", "Cid"],
+            ratings.players().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(1510.0),
+            ratings.history("Ann").first().map(|p| p.rating)
+        );
+        assert_eq!(Some(1490.0), ratings.rating("Bob"));
+        // Cid was expected to score less than half against Ann
+        assert!(ratings.rating("Cid").unwrap() > 1500.0);
+        assert_eq!(2, ratings.history("Ann").len());
+        assert!(ratings.history("Dan").is_empty());
+
+        let elo = Elo {
+            k_factor: 32.0,
+            initial: 2000.0,
+        };
+        assert_eq!(Some(1984.0), elo.rate(&games).rating("Bob"));
+    }
+
+    #[test]
+    fn performance_ratings() {
+        let games = [
+            game("Open", 1, "Ann", "Bob", GameResult::WhiteWins),
+            game("Open", 1, "Cid", "Ann", GameResult::BlackWins),
+            game("Open", 1, "Bob", "Cid", GameResult::Draw),
+            game("Blitz", 2, "Bob", "Ann", GameResult::Draw),
+        ];
+        let ratings = Elo::default().rate(&games);
+        let ann = ratings.performance("Open", "Ann").unwrap();
+        assert_eq!((2, 2.0), (ann.games, ann.score));
+        assert!((ann.average_opponent() - 1500.0).abs() < 1.0);
+        assert!((ann.rating() - 2300.0).abs() < 1.0);
+
+        let bob = ratings.performance("Blitz", "Bob").unwrap();
+        assert_eq!(bob.average_opponent(), bob.rating());
+        assert_eq!(5, ratings.performances().len());
+        assert_eq!(None, ratings.performance("Blitz", "Cid"));
+    }
+
+    #[test]
+    fn glicko2_example() {
+        // example from Glickman's description of the Glicko-2 system
+        let rating = |rating, deviation| GlickoRating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        };
+        let updated = Glicko2::default().update(
+            rating(1500.0, 200.0),
+            &[
+                (rating(1400.0, 30.0), 1.0),
+                (rating(1550.0, 100.0), 0.0),
+                (rating(1700.0, 300.0), 0.0),
+            ],
+        );
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+        assert!(
+            (updated.volatility - 0.05999).abs() < 0.00001,
+            "{:?}",
+            updated
+        );
+    }
+
+    #[test]
+    fn glicko2_periods() {
+        let games = [
+            game("Club", 1, "Ann", "Bob", GameResult::WhiteWins),
+            game("Club", 1, "Cid", "Dan", GameResult::Draw),
+            game("Cup", 2, "Ann", "Cid", GameResult::WhiteWins),
+            game("Cup", 2, "Cid", "Ann", GameResult::Unfinished),
+        ];
+        let ratings = Glicko2::default().rate(&games);
+        let ann = ratings.history("Ann");
+        assert_eq!(vec![1, 2], ann.iter().map(|p| p.period).collect::<Vec<_>>());
+        assert_eq!("Cup", ann[1].event);
+        assert!(ann[1].rating > ann[0].rating);
+        assert!(ann[1].deviation.unwrap() < ann[0].deviation.unwrap());
+
+        // Bob sat out the second period
+        let bob = ratings.history("Bob");
+        assert_eq!(1, bob.len());
+        assert!(bob[0].rating < 1500.0);
+        let cid = ratings.history("Cid");
+        assert!((cid[0].rating - 1500.0).abs() < 1e-9);
+        assert_eq!(Some(1), ratings.performance("Cup", "Ann").map(|p| p.games));
+    }
+}
@@ -26,10 +26,6 @@ impl Ray {
         }
     }
 
-    pub fn new_point(position: Position) -> Self {
-        Self::new(position, Direction::Up, 1) // direction doesn't matter here as it is a point
-    }
-
     /// Yields Vec of valid Actions for piece in starting position of the ray
     pub fn move_actions(&self, my_pieces: &Vec<Piece>, enemy_pieces: &Vec<Piece>) -> Vec<Action> {
         let has_collision = |pieces: &Vec<Piece>, pos: Position| -> bool {
@@ -133,7 +129,7 @@ impl IntoIterator for Ray {
     type Item = Position;
     type IntoIter = RayIntoIterator;
 
-    /// Hello
+    /// Iterates over at most `limit` positions following the start of the ray.
     ///
     /// # Examples
     /// ```
@@ -146,7 +142,6 @@ impl IntoIterator for Ray {
     ///
     /// assert_eq!(Some(Position::new(4, 5)), positions.next());
     /// assert_eq!(Some(Position::new(4, 6)), positions.next());
-    /// assert_eq!(Some(Position::new(4, 7)), positions.next());
     /// assert_eq!(None, positions.next());
     /// assert_eq!(None, positions.next());
     /// ```
@@ -181,7 +176,7 @@ impl Iterator for RayIntoIterator {
     type Item = Position;
 
     fn next(&mut self) -> Option<Position> {
-        if self.disposed {
+        if self.disposed || self.index > self.ray.limit {
             return None;
         }
         match self.ray.start.move_copy(self.ray.direction, self.index) {
@@ -191,7 +186,7 @@ impl Iterator for RayIntoIterator {
             }
             Err(_) => {
                 self.disposed = true;
-                return None;
+                None
             }
         }
     }
@@ -207,7 +202,7 @@ impl<'a> Iterator for RayIterator<'a> {
     type Item = Position;
 
     fn next(&mut self) -> Option<Position> {
-        if self.disposed {
+        if self.disposed || self.index > self.ray.limit {
             return None;
         }
         match self.ray.start.move_copy(self.ray.direction, self.index) {
@@ -217,7 +212,7 @@ impl<'a> Iterator for RayIterator<'a> {
             }
             Err(_) => {
                 self.disposed = true;
-                return None;
+                None
             }
         }
     }
@@ -0,0 +1,254 @@
+use super::game::GameResult;
+use std::fmt;
+
+/// Quantile of the standard normal distribution for a 95% confidence interval.
+const Z_95: f64 = 1.959_963_985;
+
+/// Wins, draws and losses of the first engine in a head-to-head match.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchStats {
+    /// Records a finished game. first_is_white tells which color the first
+    /// engine had, as colors alternate between paired games.
+    pub fn record(&mut self, result: GameResult, first_is_white: bool) {
+        match (result, first_is_white) {
+            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => self.wins += 1,
+            (GameResult::WhiteWins, false) | (GameResult::BlackWins, true) => self.losses += 1,
+            (GameResult::Draw, _) => self.draws += 1,
+            (GameResult::Unfinished, _) => {}
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Returns the mean score per game, a draw counting as half a point. An
+    /// empty match scores 0.5, as neither engine is ahead.
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(self.games())
+    }
+
+    // variance returns the variance of a single game score
+    fn variance(&self) -> f64 {
+        let (n, s) = (f64::from(self.games()), self.score());
+        (f64::from(self.wins) * (1.0 - s).powi(2)
+            + f64::from(self.draws) * (0.5 - s).powi(2)
+            + f64::from(self.losses) * s.powi(2))
+            / n
+    }
+
+    /// Returns the Elo difference of the first engine and the half width of
+    /// its 95% confidence interval. Returns None until both a win and a loss
+    /// or a draw make the estimate finite.
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let s = self.score();
+        if self.games() == 0 || s <= 0.0 || s >= 1.0 {
+            return None;
+        }
+        let margin = Z_95 * (self.variance() / f64::from(self.games())).sqrt();
+        let low = score_to_elo((s - margin).max(f64::EPSILON));
+        let high = score_to_elo((s + margin).min(1.0 - f64::EPSILON));
+        Some((score_to_elo(s), (high - low) / 2.0))
+    }
+
+    /// Returns the likelihood of superiority, i.e. the probability the first
+    /// engine is stronger, based on decisive games only.
+    pub fn los(&self) -> f64 {
+        let (wins, losses) = (f64::from(self.wins), f64::from(self.losses));
+        if wins + losses == 0.0 {
+            return 0.5;
+        }
+        0.5 * (1.0 + erf((wins - losses) / (2.0 * (wins + losses)).sqrt()))
+    }
+}
+
+impl fmt::Display for MatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "+{} ={} -{} ({} games)",
+            self.wins,
+            self.draws,
+            self.losses,
+            self.games()
+        )?;
+        if let Some((elo, error)) = self.elo() {
+            write!(f, ", Elo {:+.1} +/- {:.1}", elo, error)?;
+        }
+        write!(f, ", LOS {:.1}%", self.los() * 100.0)
+    }
+}
+
+pub fn score_to_elo(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+// erf approximates the error function (Abramowitz and Stegun 7.1.26)
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 {
+        -y
+    } else {
+        y
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SprtStatus {
+    /// The log-likelihood ratio is within bounds, more games are needed.
+    Continue(f64),
+    /// The Elo difference is elo0 rather than elo1, within the error bounds.
+    AcceptH0(f64),
+    /// The Elo difference is elo1 rather than elo0, within the error bounds.
+    AcceptH1(f64),
+}
+
+/// Sequential probability ratio test of H0: Elo = elo0 against H1: Elo = elo1,
+/// using the normal approximation of the generalized SPRT.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// Returns the lower and upper log-likelihood ratio bounds.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn llr(&self, stats: &MatchStats) -> f64 {
+        let variance = stats.variance();
+        if stats.games() == 0 || variance <= 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (elo_to_score(self.elo0), elo_to_score(self.elo1));
+        let variance = variance / f64::from(stats.games());
+        (s1 - s0) * (2.0 * stats.score() - s0 - s1) / (2.0 * variance)
+    }
+
+    pub fn status(&self, stats: &MatchStats) -> SprtStatus {
+        let llr = self.llr(stats);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtStatus::AcceptH1(llr)
+        } else if llr <= lower {
+            SprtStatus::AcceptH0(llr)
+        } else {
+            SprtStatus::Continue(llr)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(wins: u32, draws: u32, losses: u32) -> MatchStats {
+        MatchStats {
+            wins,
+            draws,
+            losses,
+        }
+    }
+
+    #[test]
+    fn record_results() {
+        let mut stats = MatchStats::default();
+        stats.record(GameResult::WhiteWins, true);
+        stats.record(GameResult::WhiteWins, false);
+        stats.record(GameResult::BlackWins, false);
+        stats.record(GameResult::Draw, true);
+        stats.record(GameResult::Unfinished, true);
+        assert_eq!(
+            MatchStats {
+                wins: 2,
+                draws: 1,
+                losses: 1
+            },
+            stats
+        );
+        assert_eq!(4, stats.games());
+    }
+
+    #[test]
+    fn elo_estimate() {
+        assert_eq!(None, stats(0, 0, 0).elo());
+        assert_eq!(0.5, stats(0, 0, 0).score());
+        assert_eq!(None, stats(5, 0, 0).elo());
+
+        let (elo, error) = stats(100, 100, 100).elo().unwrap();
+        assert!(elo.abs() < 1e-9);
+        assert!((error - 32.4).abs() < 0.5, "{}", error);
+
+        let (elo, _) = stats(76, 0, 24).elo().unwrap();
+        assert!((elo - 200.0).abs() < 1.0, "{}", elo);
+        assert!((elo_to_score(score_to_elo(0.3)) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn likelihood_of_superiority() {
+        assert_eq!(0.5, stats(0, 10, 0).los());
+        assert!((stats(10, 0, 10).los() - 0.5).abs() < 1e-9);
+        assert!(stats(60, 0, 40).los() > 0.97);
+        assert!(stats(40, 0, 60).los() < 0.03);
+    }
+
+    #[test]
+    fn sprt_decisions() {
+        let sprt = Sprt::new(0.0, 10.0);
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.001);
+        assert!((upper - 2.944).abs() < 0.001);
+
+        assert_eq!(SprtStatus::Continue(0.0), sprt.status(&stats(0, 0, 0)));
+        match sprt.status(&stats(1200, 1600, 1000)) {
+            SprtStatus::AcceptH1(llr) => assert!(llr >= upper),
+            status => panic!("unexpected {:?}", status),
+        }
+        match sprt.status(&stats(1000, 1600, 1200)) {
+            SprtStatus::AcceptH0(llr) => assert!(llr <= lower),
+            status => panic!("unexpected {:?}", status),
+        }
+        match sprt.status(&stats(105, 200, 100)) {
+            SprtStatus::Continue(llr) => assert!(llr > lower && llr < upper),
+            status => panic!("unexpected {:?}", status),
+        }
+        assert_eq!(
+            "+1 =1 -1 (3 games), Elo +0.0 +/- 561.2, LOS 50.0%",
+            stats(1, 1, 1).to_string()
+        );
+    }
+}
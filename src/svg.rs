@@ -0,0 +1,462 @@
+use super::board::Board;
+use super::pieces::Piece;
+use super::position::Position;
+use super::{Action, Color};
+use std::fmt::Write;
+
+const FILES: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+
+/// Colors used to paint an SVG diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub light: &'static str,
+    pub dark: &'static str,
+    pub last_move: &'static str,
+    pub highlight: &'static str,
+    pub arrow: &'static str,
+}
+
+impl Theme {
+    pub const BROWN: Theme = Theme {
+        light: "#f0d9b5",
+        dark: "#b58863",
+        last_move: "#cdd26a",
+        highlight: "#e06c5f",
+        arrow: "#15781b",
+    };
+    pub const BLUE: Theme = Theme {
+        light: "#dee3e6",
+        dark: "#8ca2ad",
+        last_move: "#c3d887",
+        highlight: "#e06c5f",
+        arrow: "#003088",
+    };
+    pub const GREEN: Theme = Theme {
+        light: "#ffffdd",
+        dark: "#86a666",
+        last_move: "#f6f669",
+        highlight: "#e06c5f",
+        arrow: "#882020",
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::BROWN
+    }
+}
+
+/// Standalone SVG image of a board. Positions of arrows and highlighted squares
+/// are given in absolute coordinates, as seen from White. Actions (the last move
+/// and candidate moves) are taken as the board generates them, i.e. relative to
+/// the player about to play, see `side`.
+///
+/// # Examples
+/// ```
+/// use chess::board::Board;
+/// use chess::pieces::Piece;
+/// use chess::position::Position;
+/// use chess::svg::{Svg, Theme};
+///
+/// let board = Board::new(vec![Piece::Pawn(Position::new(4, 1))], vec![]);
+/// let svg = Svg::new(&board)
+///     .theme(Theme::BLUE)
+///     .arrow(Position::new(4, 1), Position::new(4, 3))
+///     .render();
+///
+/// assert!(svg.starts_with("<svg"));
+/// assert!(svg.ends_with("</svg>\n"));
+/// ```
+pub struct Svg<'a> {
+    board: &'a Board,
+    side: Color,
+    bottom: Color,
+    coordinates: bool,
+    theme: Theme,
+    square_size: usize,
+    last_move: Option<Action>,
+    arrows: Vec<(Position, Position)>,
+    moves: Vec<Action>,
+    highlights: Vec<(Position, String)>,
+}
+
+impl<'a> Svg<'a> {
+    /// Creates a diagram with coordinates, White to play and at the bottom.
+    pub fn new(board: &'a Board) -> Self {
+        Self {
+            board,
+            side: Color::White,
+            bottom: Color::White,
+            coordinates: true,
+            theme: Theme::default(),
+            square_size: 45,
+            last_move: None,
+            arrows: vec![],
+            moves: vec![],
+            highlights: vec![],
+        }
+    }
+
+    /// Sets the color of the player about to play, i.e. the owner of my_pieces.
+    pub fn side(mut self, side: Color) -> Self {
+        self.side = side;
+        self
+    }
+
+    /// Sets which color is drawn at the bottom of the diagram.
+    pub fn orientation(mut self, bottom: Color) -> Self {
+        self.bottom = bottom;
+        self
+    }
+
+    /// Draws file letters and rank numbers on the edge squares.
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the edge length of a single square in pixels.
+    pub fn square_size(mut self, square_size: usize) -> Self {
+        self.square_size = square_size;
+        self
+    }
+
+    pub fn last_move(mut self, action: &Action) -> Self {
+        if on_board(action.source) && on_board(action.destination) {
+            self.last_move = Some(*action);
+        }
+        self
+    }
+
+    /// Draws an arrow between two squares. Off-board squares are ignored, as
+    /// for all squares given to the builder.
+    pub fn arrow(mut self, from: Position, to: Position) -> Self {
+        if on_board(from) && on_board(to) {
+            self.arrows.push((from, to));
+        }
+        self
+    }
+
+    /// Draws an arrow for each of the actions, e.g. candidate moves.
+    pub fn arrows(mut self, actions: &[Action]) -> Self {
+        self.moves.extend(
+            actions
+                .iter()
+                .filter(|a| on_board(a.source) && on_board(a.destination)),
+        );
+        self
+    }
+
+    /// Highlights a square with the theme highlight color.
+    pub fn highlight(self, position: Position) -> Self {
+        let color = self.theme.highlight.to_string();
+        self.mark(position, &color)
+    }
+
+    /// Highlights a square with a hex color like `#e06c5f` or `#f00`, or a named
+    /// SVG color like `red`. Other values are ignored, so they can't break the
+    /// markup.
+    pub fn mark(mut self, position: Position, color: &str) -> Self {
+        if on_board(position) && is_color(color) {
+            self.highlights.push((position, color.to_string()));
+        }
+        self
+    }
+
+    /// Returns the top left corner of the square in pixels.
+    fn corner(&self, pos: Position) -> (usize, usize) {
+        let (column, row) = match self.bottom {
+            Color::White => (pos.x, 7 - pos.y),
+            Color::Black => (7 - pos.x, pos.y),
+        };
+        (column * self.square_size, row * self.square_size)
+    }
+
+    fn center(&self, pos: Position) -> (f64, f64) {
+        let (x, y) = self.corner(pos);
+        let half = self.square_size as f64 / 2.0;
+        (x as f64 + half, y as f64 + half)
+    }
+
+    pub fn render(&self) -> String {
+        let size = self.square_size * 8;
+        let mut svg = String::new();
+        // writing into a String never fails
+        let _ = self.write(&mut svg, size);
+        svg
+    }
+
+    fn write(&self, svg: &mut String, size: usize) -> std::fmt::Result {
+        let sq = self.square_size;
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            size
+        )?;
+        writeln!(
+            svg,
+            r#"<defs><marker id="arrowhead" markerWidth="4" markerHeight="4" refX="2" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 z" fill="{}"/></marker></defs>"#,
+            self.theme.arrow
+        )?;
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let pos = Position::new(x, y);
+                let (px, py) = self.corner(pos);
+                let fill = if is_light(pos) {
+                    self.theme.light
+                } else {
+                    self.theme.dark
+                };
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    px, py, sq, sq, fill
+                )?;
+            }
+        }
+
+        let mut overlays: Vec<(Position, &str)> = vec![];
+        if let Some(action) = self.last_move {
+            let action = action.absolute(self.side);
+            overlays.push((action.source, self.theme.last_move));
+            overlays.push((action.destination, self.theme.last_move));
+        }
+        overlays.extend(self.highlights.iter().map(|(p, c)| (*p, c.as_str())));
+        for (pos, color) in overlays {
+            let (px, py) = self.corner(pos);
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.6"/>"#,
+                px, py, sq, sq, color
+            )?;
+        }
+
+        if self.coordinates {
+            self.write_coordinates(svg)?;
+        }
+
+        for (square, piece) in self.board.squares(self.side).iter().enumerate() {
+            if let Some((color, piece)) = piece {
+                let (px, py) = self.corner(Position::new(square % 8, square / 8));
+                match piece {
+                    Piece::Pawn(_) => self.write_pawn(svg, px as f64, py as f64, *color)?,
+                }
+            }
+        }
+
+        let moves = self.moves.iter().map(|a| a.absolute(self.side));
+        let arrows = self
+            .arrows
+            .iter()
+            .copied()
+            .chain(moves.map(|a| (a.source, a.destination)));
+        for (from, to) in arrows {
+            let (x1, y1) = self.center(from);
+            let (x2, y2) = self.center(to);
+            writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+                x1,
+                y1,
+                x2,
+                y2,
+                self.theme.arrow,
+                sq as f64 / 6.0
+            )?;
+        }
+
+        svg.push_str("</svg>\n");
+        Ok(())
+    }
+
+    fn write_coordinates(&self, svg: &mut String) -> std::fmt::Result {
+        let sq = self.square_size;
+        let font = sq as f64 / 4.0;
+        let (bottom_rank, left_file) = match self.bottom {
+            Color::White => (0, 0),
+            Color::Black => (7, 7),
+        };
+        for (i, file) in FILES.iter().enumerate() {
+            // file letters in the bottom right corner of the bottom rank
+            let pos = Position::new(i, bottom_rank);
+            let (px, py) = self.corner(pos);
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="end" fill="{}">{}</text>"#,
+                px as f64 + sq as f64 - 2.0,
+                py as f64 + sq as f64 - 3.0,
+                font,
+                self.coordinate_fill(pos),
+                file
+            )?;
+            // rank numbers in the top left corner of the left file
+            let pos = Position::new(left_file, i);
+            let (px, py) = self.corner(pos);
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}">{}</text>"#,
+                px as f64 + 2.0,
+                py as f64 + font,
+                font,
+                self.coordinate_fill(pos),
+                i + 1
+            )?;
+        }
+        Ok(())
+    }
+
+    // coordinate_fill picks the color of the opposite square so labels stay readable
+    fn coordinate_fill(&self, pos: Position) -> &'static str {
+        if is_light(pos) {
+            self.theme.dark
+        } else {
+            self.theme.light
+        }
+    }
+
+    fn write_pawn(&self, svg: &mut String, x: f64, y: f64, color: Color) -> std::fmt::Result {
+        let s = self.square_size as f64 / 45.0;
+        let (fill, stroke) = match color {
+            Color::White => ("#ffffff", "#000000"),
+            Color::Black => ("#000000", "#000000"),
+        };
+        writeln!(
+            svg,
+            r#"<g fill="{}" stroke="{}" stroke-width="{}"><circle cx="{}" cy="{}" r="{}"/><path d="M{},{} L{},{} L{},{} L{},{} z"/></g>"#,
+            fill,
+            stroke,
+            1.5 * s,
+            x + 22.5 * s,
+            y + 15.0 * s,
+            6.0 * s,
+            x + 15.0 * s,
+            y + 37.0 * s,
+            x + 19.5 * s,
+            y + 21.0 * s,
+            x + 25.5 * s,
+            y + 21.0 * s,
+            x + 30.0 * s,
+            y + 37.0 * s
+        )
+    }
+}
+
+// on_board reports whether the position is one of the 64 squares
+fn on_board(pos: Position) -> bool {
+    pos.x < 8 && pos.y < 8
+}
+
+// is_color reports whether the value is a hex color or a plain color name
+fn is_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+// is_light reports whether the square is a light one, a1 being dark
+fn is_light(pos: Position) -> bool {
+    (pos.x + pos.y) % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        Board::new(
+            vec![Piece::Pawn(Position::new(4, 1))],
+            vec![Piece::Pawn(Position::new(3, 6))],
+        )
+    }
+
+    #[test]
+    fn squares_and_pieces() {
+        let board = board();
+        let svg = Svg::new(&board).coordinates(false).render();
+        assert_eq!(64, svg.matches("<rect").count());
+        assert_eq!(2, svg.matches("<circle").count());
+        assert_eq!(1, svg.matches(r##"<g fill="#ffffff""##).count());
+        // a1 is dark and drawn in the bottom left corner
+        assert!(svg.contains(r##"<rect x="0" y="315" width="45" height="45" fill="#b58863"/>"##));
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn flipped_with_coordinates() {
+        let board = board();
+        let svg = Svg::new(&board)
+            .orientation(Color::Black)
+            .square_size(10)
+            .render();
+        // a1 is drawn in the top right corner
+        assert!(svg.contains(r##"<rect x="70" y="0" width="10" height="10" fill="#b58863"/>"##));
+        assert_eq!(16, svg.matches("<text").count());
+        assert!(svg.contains(">h</text>"));
+    }
+
+    #[test]
+    fn annotations() {
+        let board = board();
+        let last_move = Action::new(Position::new(3, 7), Position::new(3, 6));
+        let candidates = vec![
+            Action::new(Position::new(4, 1), Position::new(4, 2)),
+            Action::new(Position::new(4, 1), Position::new(4, 3)),
+        ];
+        let svg = Svg::new(&board)
+            .last_move(&last_move)
+            .arrows(&candidates)
+            .highlight(Position::new(0, 0))
+            .mark(Position::new(7, 7), "red")
+            .mark(Position::new(6, 6), "#0a0")
+            .render();
+        assert_eq!(5, svg.matches(r#"fill-opacity="0.6""#).count());
+        assert_eq!(2, svg.matches("marker-end").count());
+        assert!(svg.contains(r#"fill="red""#));
+    }
+
+    #[test]
+    fn black_to_play_actions() {
+        // Black's pawn on e7 is on e2 from Black's side
+        let board = Board::new(vec![Piece::Pawn(Position::new(4, 1))], vec![]);
+        let svg = Svg::new(&board)
+            .side(Color::Black)
+            .square_size(10)
+            .last_move(&Action::new(Position::new(4, 1), Position::new(4, 2)))
+            .arrows(&board.legal_actions())
+            .render();
+        // e7 to e6 and e7 to e5
+        assert!(svg.contains(r#"x1="45" y1="15" x2="45" y2="25""#));
+        assert!(svg.contains(r#"x1="45" y1="15" x2="45" y2="35""#));
+        assert!(svg.contains(r##"<rect x="40" y="20" width="10" height="10" fill="#cdd26a""##));
+    }
+
+    #[test]
+    fn ignored_annotations() {
+        let board = board();
+        for bottom in [Color::White, Color::Black] {
+            let svg = Svg::new(&board)
+                .orientation(bottom)
+                .last_move(&Action::new(Position::new(8, 0), Position::new(0, 0)))
+                .arrow(Position::new(0, 8), Position::new(0, 0))
+                .highlight(Position::new(0, 8))
+                .mark(Position::new(9, 9), "red")
+                .mark(Position::new(1, 1), r#"red"/><script>"#)
+                .mark(Position::new(2, 2), "#12345")
+                .mark(Position::new(3, 3), "")
+                .render();
+            assert_eq!(0, svg.matches(r#"fill-opacity="0.6""#).count());
+            assert_eq!(0, svg.matches("marker-end").count());
+            assert!(!svg.contains(r#"fill="red""#));
+            assert!(!svg.contains("<script>"));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use super::Color;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Time reserved per move for communication and bookkeeping with the GUI.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+/// Number of moves the remaining clock is spread over when movestogo is unknown.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// Share of the clock a single move may use at most, leaving a reserve for GUI
+/// and system latency. The increment only arrives after the move, so it doesn't
+/// count towards the clock.
+const MAX_MOVE_SHARE: f64 = 0.75;
+
+/// Upper bound of the soft limit scaling caused by instability and score drops.
+const MAX_EXTENSION: f64 = 2.5;
+
+/// Search limits as received from the GUI, e.g. the arguments of UCI `go`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Limits {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub infinite: bool,
+    /// Search on the opponent's time until `ponderhit`, as in UCI `go ponder`.
+    pub ponder: bool,
+}
+
+/// Decides how long the search may think about a single move.
+///
+/// The soft limit is checked between iterations: once it is exceeded no new
+/// depth should be started. The hard limit is polled inside the search and
+/// aborts the running iteration, in which case the best move of the last
+/// completed depth is played.
+///
+/// While pondering there are no limits at all, the clock limits only apply
+/// from ponderhit on.
+pub struct TimeManager {
+    start: Instant,
+    pondering: bool,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+    extension: f64,
+    last_score: Option<i32>,
+    stop: Arc<AtomicBool>,
+}
+
+impl TimeManager {
+    pub fn new(limits: &Limits, side: Color) -> Self {
+        let (soft, hard) = allocate(limits, side);
+        Self {
+            start: Instant::now(),
+            pondering: limits.ponder,
+            soft,
+            hard,
+            extension: 1.0,
+            last_score: None,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns a flag which stops the search when set, e.g. on UCI `stop`.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Switches from pondering to the real clock, which starts ticking now.
+    pub fn ponderhit(&mut self) {
+        if self.pondering {
+            self.pondering = false;
+            self.start = Instant::now();
+        }
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Soft limit currently in effect, including extensions.
+    pub fn soft_limit(&self) -> Option<Duration> {
+        if self.pondering {
+            return None;
+        }
+        let soft = self.soft?.mul_f64(self.extension);
+        Some(match self.hard {
+            Some(hard) => soft.min(hard),
+            None => soft,
+        })
+    }
+
+    pub fn hard_limit(&self) -> Option<Duration> {
+        if self.pondering {
+            return None;
+        }
+        self.hard
+    }
+
+    /// Updates the extension after an iteration completed. Time is extended
+    /// when the best move changed or the score dropped compared to the
+    /// previous iteration and slowly shrinks back while the search is stable.
+    pub fn iteration_completed(&mut self, best_move_changed: bool, score: i32) {
+        let mut extension = self.extension;
+        if best_move_changed {
+            extension *= 1.4;
+        } else {
+            extension = (extension * 0.9).max(1.0);
+        }
+        if let Some(last_score) = self.last_score {
+            let drop = last_score - score;
+            if drop >= 100 {
+                extension *= 1.5;
+            } else if drop >= 30 {
+                extension *= 1.2;
+            }
+        }
+        self.extension = extension.min(MAX_EXTENSION);
+        self.last_score = Some(score);
+    }
+
+    /// Reports whether a new iteration should be started.
+    pub fn should_start_iteration(&self) -> bool {
+        if self.stop.load(Ordering::Relaxed) {
+            return false;
+        }
+        match self.soft_limit() {
+            Some(soft) => self.elapsed() < soft,
+            None => true,
+        }
+    }
+
+    /// Reports whether the running iteration has to be aborted.
+    pub fn should_stop(&self) -> bool {
+        if self.stop.load(Ordering::Relaxed) {
+            return true;
+        }
+        match self.hard_limit() {
+            Some(hard) => self.elapsed() >= hard,
+            None => false,
+        }
+    }
+}
+
+// allocate computes the soft and hard limit for a move, None means unlimited
+fn allocate(limits: &Limits, side: Color) -> (Option<Duration>, Option<Duration>) {
+    if limits.infinite {
+        return (None, None);
+    }
+    if let Some(movetime) = limits.movetime {
+        let limit = movetime.checked_sub(MOVE_OVERHEAD).unwrap_or(movetime / 2);
+        return (Some(limit), Some(limit));
+    }
+    let (time, inc) = match side {
+        Color::White => (limits.wtime, limits.winc),
+        Color::Black => (limits.btime, limits.binc),
+    };
+    let time = match time {
+        Some(time) => time,
+        None => return (None, None),
+    };
+    let inc = inc.unwrap_or_default();
+    let available = time.checked_sub(MOVE_OVERHEAD).unwrap_or(time / 2);
+    let moves_to_go = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+    let max = available.mul_f64(MAX_MOVE_SHARE);
+    let soft = available / moves_to_go + inc * 3 / 4;
+    let hard = if moves_to_go == 1 {
+        max
+    } else {
+        (soft * 4).min(available / 2).max(soft).min(max)
+    };
+    (Some(soft.min(hard)), Some(hard))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn infinite_and_empty_limits() {
+        let limits = Limits {
+            infinite: true,
+            wtime: Some(ms(1000)),
+            ..Limits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        assert_eq!(None, tm.soft_limit());
+        assert_eq!(None, tm.hard_limit());
+        assert!(!tm.should_stop());
+
+        let tm = TimeManager::new(&Limits::default(), Color::Black);
+        assert_eq!(None, tm.hard_limit());
+        assert!(tm.should_start_iteration());
+    }
+
+    #[test]
+    fn movetime_limits() {
+        let limits = Limits {
+            movetime: Some(ms(1000)),
+            ..Limits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        assert_eq!(Some(ms(970)), tm.soft_limit());
+        assert_eq!(Some(ms(970)), tm.hard_limit());
+    }
+
+    #[test]
+    fn clock_limits() {
+        let limits = Limits {
+            wtime: Some(ms(60_030)),
+            btime: Some(ms(1_030)),
+            winc: Some(ms(1000)),
+            ..Limits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        assert_eq!(Some(ms(2750)), tm.soft_limit());
+        assert_eq!(Some(ms(11_000)), tm.hard_limit());
+
+        let tm = TimeManager::new(&limits, Color::Black);
+        assert!(tm.hard_limit().unwrap() <= ms(1000));
+
+        let limits = Limits {
+            btime: Some(ms(10_030)),
+            movestogo: Some(1),
+            ..Limits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::Black);
+        assert_eq!(Some(ms(7500)), tm.soft_limit());
+        assert_eq!(Some(ms(7500)), tm.hard_limit());
+    }
+
+    #[test]
+    fn increment_larger_than_clock() {
+        // the increment is only added after the move, so it can't be spent
+        let limits = Limits {
+            wtime: Some(ms(1030)),
+            winc: Some(ms(1000)),
+            movestogo: Some(2),
+            ..Limits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        assert_eq!(Some(ms(750)), tm.soft_limit());
+        assert_eq!(Some(ms(750)), tm.hard_limit());
+
+        let limits = Limits {
+            btime: Some(ms(530)),
+            binc: Some(ms(2000)),
+            ..Limits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::Black);
+        assert_eq!(Some(ms(375)), tm.soft_limit());
+        assert_eq!(Some(ms(375)), tm.hard_limit());
+    }
+
+    #[test]
+    fn extension_on_instability() {
+        let limits = Limits {
+            wtime: Some(ms(60_030)),
+            ..Limits::default()
+        };
+        let mut tm = TimeManager::new(&limits, Color::White);
+        let base = tm.soft_limit().unwrap();
+        tm.iteration_completed(false, 20);
+        assert_eq!(base, tm.soft_limit().unwrap());
+        tm.iteration_completed(true, -80);
+        assert!(tm.soft_limit().unwrap() > base);
+        for _ in 0..10 {
+            tm.iteration_completed(true, -500);
+        }
+        assert!(tm.soft_limit().unwrap() <= tm.hard_limit().unwrap());
+    }
+
+    #[test]
+    fn stop_flag() {
+        let tm = TimeManager::new(&Limits::default(), Color::White);
+        assert!(!tm.should_stop());
+        tm.stop_handle().store(true, Ordering::Relaxed);
+        assert!(tm.should_stop());
+        assert!(!tm.should_start_iteration());
+    }
+
+    #[test]
+    fn ponderhit_switches_to_clock() {
+        let limits = Limits {
+            wtime: Some(ms(60_030)),
+            ponder: true,
+            ..Limits::default()
+        };
+        let mut tm = TimeManager::new(&limits, Color::White);
+        assert!(tm.is_pondering());
+        assert_eq!(None, tm.soft_limit());
+        assert_eq!(None, tm.hard_limit());
+        assert!(!tm.should_stop());
+
+        tm.ponderhit();
+        assert!(!tm.is_pondering());
+        assert_eq!(Some(ms(2000)), tm.soft_limit());
+        assert_eq!(Some(ms(8000)), tm.hard_limit());
+        assert!(tm.elapsed() < ms(1000));
+    }
+}
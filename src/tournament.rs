@@ -0,0 +1,922 @@
+use super::errors::Error;
+use super::game::{pgn_tag, GameResult};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Maximum number of pairing attempts before a Swiss pairing rule is relaxed.
+const PAIRING_BUDGET: usize = 100_000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Everyone plays everyone `cycles` times, paired by Berger tables.
+    RoundRobin { cycles: usize },
+    /// Swiss system with Dutch pairings.
+    Swiss { rounds: usize },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    pub rating: Option<u32>,
+}
+
+impl Player {
+    pub fn new(name: &str, rating: Option<u32>) -> Self {
+        Self {
+            name: name.to_string(),
+            rating,
+        }
+    }
+}
+
+/// Single board of a round. A pairing without a black player is a bye,
+/// which scores a full point for white.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pairing {
+    pub white: usize,
+    pub black: Option<usize>,
+    pub result: GameResult,
+}
+
+impl Pairing {
+    fn game(white: usize, black: usize) -> Self {
+        Self {
+            white,
+            black: Some(black),
+            result: GameResult::Unfinished,
+        }
+    }
+
+    fn bye(player: usize) -> Self {
+        Self {
+            white: player,
+            black: None,
+            result: GameResult::WhiteWins,
+        }
+    }
+
+    // points returns the points scored by the player in this pairing, if finished
+    fn points(&self, player: usize) -> Option<f64> {
+        let white = player == self.white;
+        match (self.result, white) {
+            (GameResult::Unfinished, _) => None,
+            (GameResult::Draw, _) => Some(0.5),
+            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => Some(1.0),
+            (GameResult::WhiteWins, false) | (GameResult::BlackWins, true) => Some(0.0),
+        }
+    }
+
+    fn opponent(&self, player: usize) -> Option<usize> {
+        if player == self.white {
+            self.black
+        } else {
+            Some(self.white)
+        }
+    }
+
+    fn involves(&self, player: usize) -> bool {
+        self.white == player || self.black == Some(player)
+    }
+}
+
+/// Score and tiebreaks of a player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub player: usize,
+    pub score: f64,
+    pub buchholz: f64,
+    pub sonneborn_berger: f64,
+}
+
+/// Round-robin or Swiss tournament. Players are referred to by their index,
+/// which is also their Berger number in round robins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tournament {
+    pub name: String,
+    format: Format,
+    players: Vec<Player>,
+    rounds: Vec<Vec<Pairing>>,
+}
+
+impl Tournament {
+    pub fn new(name: &str, format: Format, players: Vec<Player>) -> Self {
+        Self {
+            name: name.to_string(),
+            format,
+            players,
+            rounds: vec![],
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn rounds(&self) -> &[Vec<Pairing>] {
+        &self.rounds
+    }
+
+    pub fn total_rounds(&self) -> usize {
+        match self.format {
+            Format::RoundRobin { cycles } => {
+                let n = self.players.len() + self.players.len() % 2;
+                cycles * n.saturating_sub(1)
+            }
+            Format::Swiss { rounds } => rounds,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.rounds.len() == self.total_rounds() && self.round_finished()
+    }
+
+    fn round_finished(&self) -> bool {
+        self.rounds.last().is_none_or(|round| {
+            round
+                .iter()
+                .all(|pairing| pairing.result != GameResult::Unfinished)
+        })
+    }
+
+    /// Pairs the next round. All games of the current round need a result.
+    pub fn pair_next_round(&mut self) -> Result<&[Pairing], Error> {
+        if !self.round_finished() {
+            return Err(Error::RoundNotFinished);
+        }
+        if self.rounds.len() >= self.total_rounds() {
+            return Err(Error::TournamentFinished);
+        }
+        let round = match self.format {
+            Format::RoundRobin { .. } => self.berger_round(),
+            Format::Swiss { .. } => self.swiss_round(),
+        };
+        self.rounds.push(round);
+        Ok(self.rounds.last().unwrap())
+    }
+
+    /// Records a result of a game given by 1-based round and board numbers.
+    pub fn record_result(
+        &mut self,
+        round: usize,
+        board: usize,
+        result: GameResult,
+    ) -> Result<(), Error> {
+        let pairing = self
+            .rounds
+            .get_mut(round.wrapping_sub(1))
+            .and_then(|round| round.get_mut(board.wrapping_sub(1)))
+            .filter(|pairing| pairing.black.is_some())
+            .ok_or(Error::UnknownGame)?;
+        pairing.result = result;
+        Ok(())
+    }
+
+    fn pairings(&self) -> impl Iterator<Item = &Pairing> {
+        self.rounds.iter().flatten()
+    }
+
+    pub fn score(&self, player: usize) -> f64 {
+        self.pairings()
+            .filter(|pairing| pairing.involves(player))
+            .filter_map(|pairing| pairing.points(player))
+            .sum()
+    }
+
+    /// Returns players sorted by score, Buchholz, Sonneborn-Berger and
+    /// starting rank. Byes and unfinished games don't count for tiebreaks.
+    pub fn standings(&self) -> Vec<Standing> {
+        let scores: Vec<f64> = (0..self.players.len()).map(|p| self.score(p)).collect();
+        let mut standings: Vec<Standing> = (0..self.players.len())
+            .map(|player| {
+                let mut standing = Standing {
+                    player,
+                    score: scores[player],
+                    buchholz: 0.0,
+                    sonneborn_berger: 0.0,
+                };
+                for pairing in self.pairings().filter(|p| p.involves(player)) {
+                    if let (Some(opponent), Some(points)) =
+                        (pairing.opponent(player), pairing.points(player))
+                    {
+                        standing.buchholz += scores[opponent];
+                        standing.sonneborn_berger += points * scores[opponent];
+                    }
+                }
+                standing
+            })
+            .collect();
+        let rank = self.starting_ranks();
+        standings.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(
+                    b.buchholz
+                        .partial_cmp(&a.buchholz)
+                        .unwrap_or(Ordering::Equal),
+                )
+                .then(
+                    b.sonneborn_berger
+                        .partial_cmp(&a.sonneborn_berger)
+                        .unwrap_or(Ordering::Equal),
+                )
+                .then(rank[a.player].cmp(&rank[b.player]))
+        });
+        standings
+    }
+
+    // starting_ranks returns the rank of each player by rating, unrated last
+    fn starting_ranks(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.players.len()).collect();
+        order.sort_by_key(|&p| std::cmp::Reverse(self.players[p].rating));
+        let mut ranks = vec![0; self.players.len()];
+        for (rank, player) in order.into_iter().enumerate() {
+            ranks[player] = rank;
+        }
+        ranks
+    }
+
+    fn berger_round(&self) -> Vec<Pairing> {
+        let players = self.players.len();
+        let n = players + players % 2;
+        let m = n - 1;
+        let (cycle, r) = (self.rounds.len() / m, self.rounds.len() % m);
+        let p = r * (n / 2) % m;
+
+        let mut boards = vec![if r % 2 == 0 { (p, m) } else { (m, p) }];
+        boards.extend((1..n / 2).map(|k| ((p + k) % m, (p + m - k) % m)));
+        boards
+            .into_iter()
+            .map(|(white, black)| {
+                if cycle % 2 == 1 {
+                    (black, white)
+                } else {
+                    (white, black)
+                }
+            })
+            .map(|(white, black)| {
+                // the index past the last player stands for the bye with an odd field
+                if white == players {
+                    Pairing::bye(black)
+                } else if black == players {
+                    Pairing::bye(white)
+                } else {
+                    Pairing::game(white, black)
+                }
+            })
+            .collect()
+    }
+
+    fn swiss_round(&self) -> Vec<Pairing> {
+        let ranks = self.starting_ranks();
+        let scores: Vec<f64> = (0..self.players.len()).map(|p| self.score(p)).collect();
+        let mut order: Vec<usize> = (0..self.players.len()).collect();
+        order.sort_by(|&a, &b| {
+            scores[b]
+                .partial_cmp(&scores[a])
+                .unwrap_or(Ordering::Equal)
+                .then(ranks[a].cmp(&ranks[b]))
+        });
+
+        let mut round = vec![];
+        if order.len() % 2 == 1 {
+            let had_bye = |p: usize| self.pairings().any(|g| g.white == p && g.black.is_none());
+            let index = order
+                .iter()
+                .rposition(|&p| !had_bye(p))
+                .unwrap_or(order.len() - 1);
+            round.push(Pairing::bye(order.remove(index)));
+        }
+
+        let mut played = HashSet::new();
+        for pairing in self.pairings() {
+            if let Some(black) = pairing.black {
+                played.insert((pairing.white, black));
+                played.insert((black, pairing.white));
+            }
+        }
+        let histories: Vec<ColorHistory> = (0..self.players.len())
+            .map(|p| self.color_history(p))
+            .collect();
+        let colors_fit = |a: usize, b: usize| colors(&histories[a], &histories[b], 0).is_some();
+        let fresh = |a: usize, b: usize| !played.contains(&(a, b));
+        // rematches and then the color rules are given up only when there is
+        // no other way to pair the round
+        let pairs = pair_swiss(&order, &scores, &|a, b| fresh(a, b) && colors_fit(a, b))
+            .or_else(|| pair_swiss(&order, &scores, &colors_fit))
+            .or_else(|| pair_swiss(&order, &scores, &|_, _| true))
+            .unwrap_or_default();
+
+        let mut games: Vec<Pairing> = pairs
+            .into_iter()
+            .enumerate()
+            .map(|(board, (a, b))| {
+                let (history_a, history_b) = (&histories[a], &histories[b]);
+                let white = colors(history_a, history_b, board)
+                    .unwrap_or_else(|| preference(history_a, history_b, board));
+                if white {
+                    Pairing::game(a, b)
+                } else {
+                    Pairing::game(b, a)
+                }
+            })
+            .collect();
+        games.append(&mut round);
+        games
+    }
+
+    fn color_history(&self, player: usize) -> ColorHistory {
+        let mut history = ColorHistory::default();
+        for pairing in self.pairings().filter(|p| p.black.is_some()) {
+            let white = if pairing.white == player {
+                true
+            } else if pairing.black == Some(player) {
+                false
+            } else {
+                continue;
+            };
+            history.difference += if white { 1 } else { -1 };
+            history.streak = if history.last == Some(white) {
+                history.streak + 1
+            } else {
+                1
+            };
+            history.last = Some(white);
+        }
+        history
+    }
+
+    /// Returns a text crosstable ordered by standings. Each round cell shows
+    /// the result (+ = - or * if unfinished), opponent's place and color.
+    pub fn crosstable(&self) -> String {
+        let standings = self.standings();
+        let mut place = vec![0; self.players.len()];
+        for (i, standing) in standings.iter().enumerate() {
+            place[standing.player] = i + 1;
+        }
+        let width = self
+            .players
+            .iter()
+            .map(|p| p.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(6);
+
+        let mut table = String::new();
+        let _ = write!(
+            table,
+            "{:>3}  {:<width$}  {:>6}",
+            "#",
+            "Player",
+            "Rating",
+            width = width
+        );
+        for round in 1..=self.rounds.len() {
+            let _ = write!(table, "  {:>4}", format!("R{}", round));
+        }
+        let _ = writeln!(table, "  {:>5}  {:>5}  {:>5}", "Score", "Bh", "SB");
+
+        for (i, standing) in standings.iter().enumerate() {
+            let player = &self.players[standing.player];
+            let rating = player.rating.map_or("-".to_string(), |r| r.to_string());
+            let _ = write!(
+                table,
+                "{:>3}  {:<width$}  {:>6}",
+                i + 1,
+                player.name,
+                rating,
+                width = width
+            );
+            for round in self.rounds.iter() {
+                let cell = match round.iter().find(|p| p.involves(standing.player)) {
+                    Some(pairing) => match pairing.opponent(standing.player) {
+                        None => "bye".to_string(),
+                        Some(opponent) => {
+                            let half_points = pairing.points(standing.player).map(|p| p * 2.0);
+                            let result = match half_points.map(|p| p as u8) {
+                                Some(2) => '+',
+                                Some(1) => '=',
+                                Some(_) => '-',
+                                None => '*',
+                            };
+                            let color = if pairing.white == standing.player {
+                                'w'
+                            } else {
+                                'b'
+                            };
+                            format!("{}{}{}", result, place[opponent], color)
+                        }
+                    },
+                    None => ".".to_string(),
+                };
+                let _ = write!(table, "  {:>4}", cell);
+            }
+            let _ = writeln!(
+                table,
+                "  {:>5.1}  {:>5.1}  {:>5.2}",
+                standing.score, standing.buchholz, standing.sonneborn_berger
+            );
+        }
+        table
+    }
+
+    /// Exports all games, byes excluded, as PGN with headers and results.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (round, pairings) in self.rounds.iter().enumerate() {
+            for pairing in pairings.iter() {
+                let black = match pairing.black {
+                    Some(black) => &self.players[black],
+                    None => continue,
+                };
+                let white = &self.players[pairing.white];
+                pgn.push_str(&pgn_tag("Event", &self.name));
+                pgn.push_str(&pgn_tag("Site", "?"));
+                pgn.push_str(&pgn_tag("Date", "????.??.??"));
+                pgn.push_str(&pgn_tag("Round", round + 1));
+                pgn.push_str(&pgn_tag("White", &white.name));
+                pgn.push_str(&pgn_tag("Black", &black.name));
+                pgn.push_str(&pgn_tag("Result", pairing.result));
+                if let Some(rating) = white.rating {
+                    pgn.push_str(&pgn_tag("WhiteElo", rating));
+                }
+                if let Some(rating) = black.rating {
+                    pgn.push_str(&pgn_tag("BlackElo", rating));
+                }
+                let _ = writeln!(pgn, "\n{}\n", pairing.result);
+            }
+        }
+        pgn
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.encode())?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::decode(&fs::read_to_string(path)?)
+    }
+
+    // encode writes the tournament state in a line based text format
+    fn encode(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "name {}", self.name);
+        let _ = match self.format {
+            Format::RoundRobin { cycles } => writeln!(out, "format round-robin {}", cycles),
+            Format::Swiss { rounds } => writeln!(out, "format swiss {}", rounds),
+        };
+        for player in self.players.iter() {
+            let rating = player.rating.map_or("-".to_string(), |r| r.to_string());
+            let _ = writeln!(out, "player {} {}", rating, player.name);
+        }
+        for round in self.rounds.iter() {
+            let _ = writeln!(out, "round");
+            for pairing in round.iter() {
+                let _ = match pairing.black {
+                    Some(black) => {
+                        writeln!(out, "game {} {} {}", pairing.white, black, pairing.result)
+                    }
+                    None => writeln!(out, "bye {}", pairing.white),
+                };
+            }
+        }
+        out
+    }
+
+    fn decode(s: &str) -> Result<Self, Error> {
+        let mut name = None;
+        let mut format = None;
+        let mut players = vec![];
+        let mut rounds: Vec<Vec<Pairing>> = vec![];
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let (keyword, rest) = split_word(line);
+            match keyword {
+                "name" => name = Some(rest.to_string()),
+                "format" => {
+                    let (kind, count) = split_word(rest);
+                    let count = count.parse().map_err(|_| Error::InvalidTournament)?;
+                    format = Some(match kind {
+                        "round-robin" => Format::RoundRobin { cycles: count },
+                        "swiss" => Format::Swiss { rounds: count },
+                        _ => return Err(Error::InvalidTournament),
+                    });
+                }
+                "player" => {
+                    let (rating, name) = split_word(rest);
+                    let rating = match rating {
+                        "-" => None,
+                        r => Some(r.parse().map_err(|_| Error::InvalidTournament)?),
+                    };
+                    players.push(Player::new(name, rating));
+                }
+                "round" => rounds.push(vec![]),
+                "game" | "bye" => {
+                    let fields: Vec<&str> = rest.split_whitespace().collect();
+                    let index = |field: &str| -> Result<usize, Error> {
+                        field
+                            .parse()
+                            .ok()
+                            .filter(|&i| i < players.len())
+                            .ok_or(Error::InvalidTournament)
+                    };
+                    let pairing = match (keyword, fields.as_slice()) {
+                        ("game", [white, black, result]) => Pairing {
+                            white: index(white)?,
+                            black: Some(index(black)?),
+                            result: parse_result(result)?,
+                        },
+                        ("bye", [player]) => Pairing::bye(index(player)?),
+                        _ => return Err(Error::InvalidTournament),
+                    };
+                    rounds
+                        .last_mut()
+                        .ok_or(Error::InvalidTournament)?
+                        .push(pairing);
+                }
+                _ => return Err(Error::InvalidTournament),
+            }
+        }
+        Ok(Self {
+            name: name.ok_or(Error::InvalidTournament)?,
+            format: format.ok_or(Error::InvalidTournament)?,
+            players,
+            rounds,
+        })
+    }
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    match s.find(' ') {
+        Some(i) => (&s[..i], s[i + 1..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+fn parse_result(s: &str) -> Result<GameResult, Error> {
+    match s {
+        "1-0" => Ok(GameResult::WhiteWins),
+        "0-1" => Ok(GameResult::BlackWins),
+        "1/2-1/2" => Ok(GameResult::Draw),
+        "*" => Ok(GameResult::Unfinished),
+        _ => Err(Error::InvalidTournament),
+    }
+}
+
+// ColorHistory sums up the colors a player had in games, byes aside
+#[derive(Copy, Clone, Debug, Default)]
+struct ColorHistory {
+    /// Games with white minus games with black.
+    difference: i32,
+    /// Whether the last game was played with white.
+    last: Option<bool>,
+    /// Number of games in a row with the last color.
+    streak: u32,
+}
+
+impl ColorHistory {
+    // allows reports whether the player may get the color under the absolute
+    // rules: never more than two whites or blacks ahead and never three in a row
+    fn allows(&self, white: bool) -> bool {
+        let difference = self.difference + if white { 1 } else { -1 };
+        difference.abs() <= 2 && !(self.last == Some(white) && self.streak >= 2)
+    }
+}
+
+// colors decides whether the higher placed player a gets white against b,
+// None if every assignment breaks the absolute color rules
+fn colors(a: &ColorHistory, b: &ColorHistory, board: usize) -> Option<bool> {
+    let white = a.allows(true) && b.allows(false);
+    let black = a.allows(false) && b.allows(true);
+    match (white, black) {
+        (true, true) => Some(preference(a, b, board)),
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    }
+}
+
+// preference balances the colors of a and b and alternates them where
+// possible, returning whether a should get white
+fn preference(a: &ColorHistory, b: &ColorHistory, board: usize) -> bool {
+    match a.difference.cmp(&b.difference) {
+        Ordering::Less => true,
+        Ordering::Greater => false,
+        Ordering::Equal => match (a.last, b.last) {
+            (Some(last_a), _) => !last_a,
+            (None, Some(last_b)) => last_b,
+            (None, None) => board % 2 != 1,
+        },
+    }
+}
+
+// pair_swiss pairs players ordered by score and rank following the Dutch
+// system: within a score group the top half plays the bottom half in order,
+// transpositions avoid incompatible opponents and an odd player floats down
+// to the next group. Returns None when no pairing satisfies compatible or
+// the search takes too long.
+fn pair_swiss(
+    players: &[usize],
+    scores: &[f64],
+    compatible: &dyn Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    pair_group(players, scores, compatible, &mut PAIRING_BUDGET.clone())
+}
+
+fn pair_group(
+    players: &[usize],
+    scores: &[f64],
+    compatible: &dyn Fn(usize, usize) -> bool,
+    budget: &mut usize,
+) -> Option<Vec<(usize, usize)>> {
+    if players.is_empty() {
+        return Some(vec![]);
+    }
+    if *budget == 0 {
+        return None;
+    }
+    *budget -= 1;
+
+    let first = players[0];
+    let group = players
+        .iter()
+        .take_while(|&&p| scores[p] == scores[first])
+        .count();
+    let half = group / 2;
+    let candidates: Vec<usize> = if group >= 2 {
+        (half..group)
+            .chain(1..half)
+            .chain(group..players.len())
+            .collect()
+    } else {
+        (1..players.len()).collect()
+    };
+    for candidate in candidates {
+        let opponent = players[candidate];
+        if !compatible(first, opponent) {
+            continue;
+        }
+        let rest: Vec<usize> = players[1..]
+            .iter()
+            .copied()
+            .filter(|&p| p != opponent)
+            .collect();
+        if let Some(mut pairs) = pair_group(&rest, scores, compatible, budget) {
+            pairs.insert(0, (first, opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players(n: usize) -> Vec<Player> {
+        (0..n)
+            .map(|i| Player::new(&format!("Player {}", i + 1), Some(2400 - i as u32 * 50)))
+            .collect()
+    }
+
+    fn boards(round: &[Pairing]) -> Vec<(usize, Option<usize>)> {
+        round.iter().map(|p| (p.white, p.black)).collect()
+    }
+
+    fn finish_round(tournament: &mut Tournament, results: &[GameResult]) {
+        let round = tournament.rounds().len();
+        for (board, &result) in results.iter().enumerate() {
+            tournament.record_result(round, board + 1, result).unwrap();
+        }
+    }
+
+    #[test]
+    fn berger_tables() {
+        let mut tournament = Tournament::new("RR", Format::RoundRobin { cycles: 1 }, players(6));
+        let mut rounds = vec![];
+        while let Ok(round) = tournament.pair_next_round() {
+            rounds.push(boards(round));
+            let games = round.len();
+            finish_round(&mut tournament, &vec![GameResult::Draw; games]);
+        }
+        let game = |w: usize, b: usize| (w - 1, Some(b - 1));
+        assert_eq!(
+            vec![
+                vec![game(1, 6), game(2, 5), game(3, 4)],
+                vec![game(6, 4), game(5, 3), game(1, 2)],
+                vec![game(2, 6), game(3, 1), game(4, 5)],
+                vec![game(6, 5), game(1, 4), game(2, 3)],
+                vec![game(3, 6), game(4, 2), game(5, 1)],
+            ],
+            rounds
+        );
+        assert!(tournament.is_finished());
+    }
+
+    #[test]
+    fn odd_double_round_robin() {
+        let mut tournament = Tournament::new("RR", Format::RoundRobin { cycles: 2 }, players(3));
+        assert_eq!(6, tournament.total_rounds());
+        let mut games = HashSet::new();
+        while let Ok(round) = tournament.pair_next_round() {
+            assert_eq!(1, round.iter().filter(|p| p.black.is_none()).count());
+            for pairing in round.iter() {
+                if let Some(black) = pairing.black {
+                    assert!(games.insert((pairing.white, black)));
+                }
+            }
+            let round = tournament.rounds().len();
+            let board = tournament.rounds()[round - 1]
+                .iter()
+                .position(|p| p.black.is_some())
+                .unwrap();
+            tournament
+                .record_result(round, board + 1, GameResult::WhiteWins)
+                .unwrap();
+        }
+        // every pair met once with each color
+        assert_eq!(6, games.len());
+        assert_eq!(4.0, tournament.score(0));
+    }
+
+    #[test]
+    fn round_flow_errors() {
+        let mut tournament = Tournament::new("RR", Format::RoundRobin { cycles: 1 }, players(2));
+        tournament.pair_next_round().unwrap();
+        assert!(matches!(
+            tournament.pair_next_round(),
+            Err(Error::RoundNotFinished)
+        ));
+        assert!(tournament.record_result(1, 2, GameResult::Draw).is_err());
+        assert!(tournament.record_result(0, 1, GameResult::Draw).is_err());
+        tournament.record_result(1, 1, GameResult::Draw).unwrap();
+        assert!(matches!(
+            tournament.pair_next_round(),
+            Err(Error::TournamentFinished)
+        ));
+    }
+
+    #[test]
+    fn tiebreaks() {
+        use GameResult::*;
+        let mut tournament = Tournament::new("RR", Format::RoundRobin { cycles: 1 }, players(4));
+        for results in [[WhiteWins, Draw], [BlackWins, Draw], [WhiteWins, BlackWins]].iter() {
+            tournament.pair_next_round().unwrap();
+            finish_round(&mut tournament, results);
+        }
+        let standings = tournament.standings();
+        let table: Vec<(usize, f64, f64, f64)> = standings
+            .iter()
+            .map(|s| (s.player, s.score, s.buchholz, s.sonneborn_berger))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 2.5, 3.5, 2.5),
+                (1, 2.0, 4.0, 2.0),
+                (2, 1.5, 4.5, 1.0),
+                (3, 0.0, 6.0, 0.0),
+            ],
+            table
+        );
+
+        let crosstable = tournament.crosstable();
+        let lines: Vec<&str> = crosstable.lines().collect();
+        assert_eq!(
+            "  #  Player    Rating    R1    R2    R3  Score     Bh     SB",
+            lines[0]
+        );
+        assert_eq!(
+            "  1  Player 1    2400   +4w   =2w   +3b    2.5    3.5   2.50",
+            lines[1]
+        );
+
+        let pgn = tournament.to_pgn();
+        assert_eq!(6, pgn.matches("[Event \"RR\"]").count());
+        assert!(pgn.contains(
+            "[Round \"2\"]\n[White \"Player 4\"]\n[Black \"Player 3\"]\n[Result \"0-1\"]\n"
+        ));
+        assert!(pgn.contains("[BlackElo \"2350\"]\n\n1/2-1/2\n"));
+    }
+
+    #[test]
+    fn swiss_pairings() {
+        let mut tournament = Tournament::new("Swiss", Format::Swiss { rounds: 4 }, players(7));
+        // first round pairs the top half against the bottom half by rating
+        let round = boards(tournament.pair_next_round().unwrap());
+        assert_eq!(
+            vec![(0, Some(3)), (4, Some(1)), (2, Some(5)), (6, None)],
+            round
+        );
+
+        let mut games = HashSet::new();
+        let mut byes = HashSet::new();
+        loop {
+            let round = tournament.rounds().last().unwrap().clone();
+            for (board, pairing) in round.iter().enumerate() {
+                match pairing.black {
+                    Some(black) => {
+                        assert!(games.insert((pairing.white.min(black), pairing.white.max(black))));
+                        // the higher rated player wins
+                        let result = if pairing.white < black {
+                            GameResult::WhiteWins
+                        } else {
+                            GameResult::BlackWins
+                        };
+                        let number = tournament.rounds().len();
+                        tournament.record_result(number, board + 1, result).unwrap();
+                    }
+                    None => assert!(byes.insert(pairing.white)),
+                }
+            }
+            if tournament.pair_next_round().is_err() {
+                break;
+            }
+        }
+        assert!(tournament.is_finished());
+        assert_eq!(4, byes.len());
+        assert_eq!(0, tournament.standings()[0].player);
+        assert_eq!(4.0, tournament.score(0));
+        assert_color_rules(&tournament);
+    }
+
+    // assert_color_rules checks that no player got the same color three
+    // times in a row or two more games with one color than the other
+    fn assert_color_rules(tournament: &Tournament) {
+        for player in 0..tournament.players().len() {
+            let colors: Vec<bool> = tournament
+                .rounds()
+                .iter()
+                .flatten()
+                .filter(|p| p.black.is_some() && p.involves(player))
+                .map(|p| p.white == player)
+                .collect();
+            assert!(
+                colors.windows(3).all(|w| w[0] != w[1] || w[1] != w[2]),
+                "player {} got {:?}",
+                player,
+                colors
+            );
+            let whites = colors.iter().filter(|&&white| white).count() as i32;
+            let difference = 2 * whites - colors.len() as i32;
+            assert!(difference.abs() <= 2, "player {} got {:?}", player, colors);
+        }
+    }
+
+    #[test]
+    fn swiss_color_rules() {
+        let mut tournament = Tournament::new("Swiss", Format::Swiss { rounds: 5 }, players(5));
+        while let Ok(round) = tournament.pair_next_round() {
+            let results: Vec<GameResult> = round
+                .iter()
+                .filter(|p| p.black.is_some())
+                .map(|p| {
+                    if p.white < p.black.unwrap() {
+                        GameResult::WhiteWins
+                    } else {
+                        GameResult::BlackWins
+                    }
+                })
+                .collect();
+            finish_round(&mut tournament, &results);
+        }
+        assert!(tournament.is_finished());
+        assert_color_rules(&tournament);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut tournament = Tournament::new(
+            "Club Championship 2026",
+            Format::Swiss { rounds: 3 },
+            vec![
+                Player::new("Ann Smith", Some(1800)),
+                Player::new("Bob", None),
+                Player::new("Cid", Some(1500)),
+            ],
+        );
+        tournament.pair_next_round().unwrap();
+        tournament.record_result(1, 1, GameResult::Draw).unwrap();
+        tournament.pair_next_round().unwrap();
+
+        let encoded = tournament.encode();
+        assert_eq!(tournament, Tournament::decode(&encoded).unwrap());
+
+        let path = std::env::temp_dir().join(format!("tournament-{}.txt", std::process::id()));
+        tournament.save(&path).unwrap();
+        let loaded = Tournament::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(tournament, loaded);
+
+        assert!(Tournament::decode("name x\nformat swiss 3\ngame 0 1 1-0\n").is_err());
+        assert!(Tournament::decode("name x\nformat chaos 3\n").is_err());
+        assert!(Tournament::decode("format swiss 3\n").is_err());
+    }
+}